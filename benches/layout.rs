@@ -0,0 +1,90 @@
+// cargo bench --no-default-features --bench layout
+//
+// Times one frame of the layout, pushing, pulling and moving every node, on random graphs. The
+// exact double loop the push forces used before Barnes–Hut runs on a copy of the same graph.
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use projekt::constants::{
+    PULL_FORCE_FORCE_AT_TWICE_DISTANCE, PULL_FORCE_MIN_DISTANCE, PUSH_FORCE_DISTANCE,
+    PUSH_FORCE_FORCE,
+};
+use projekt::graph::generators::random::{generate, RandomModel, RandomOptions};
+use projekt::graph::gravity::{PullForceConfig, PushForceConfig};
+use projekt::graph::{Graph, GraphOnCanvas, Position};
+
+const DELTA: f32 = 1. / 60.;
+const WARM_UP: u32 = 3;
+const ITERATIONS: u32 = 10;
+
+fn exact_step_layout(
+    graph: &mut Graph,
+    push_conf: &PushForceConfig,
+    pull_conf: &PullForceConfig,
+    rng: &mut StdRng,
+) {
+    let indices = graph
+        .node_indices()
+        .filter(|idx| !graph[*idx].is_hidden())
+        .collect::<Vec<_>>();
+    let positions = indices
+        .iter()
+        .map(|idx| graph[*idx].position())
+        .collect::<Vec<_>>();
+    for (nr, idx) in indices.iter().enumerate() {
+        let force: Position = positions
+            .iter()
+            .enumerate()
+            .filter(|(other_nr, _)| *other_nr != nr)
+            .map(|(_, other)| push_conf.force_between(positions[nr], *other, rng))
+            .sum();
+        graph[*idx].add_force(force);
+    }
+    graph.pull_force(pull_conf);
+
+    for node in graph.node_weights_mut() {
+        node.consume_force(DELTA);
+    }
+}
+
+// Average time of one step after a few steps to warm up.
+fn time_steps(graph: &Graph, mut step: impl FnMut(&mut Graph)) -> Duration {
+    let mut graph = graph.clone();
+    for _ in 0..WARM_UP {
+        step(&mut graph);
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        step(&mut graph);
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let push_conf = PushForceConfig::new(PUSH_FORCE_FORCE, PUSH_FORCE_DISTANCE);
+    let pull_conf =
+        PullForceConfig::new(PULL_FORCE_MIN_DISTANCE, PULL_FORCE_FORCE_AT_TWICE_DISTANCE);
+
+    for nodes in [100, 1_000, 10_000] {
+        let model = RandomModel::Gnm {
+            nodes,
+            edges: 2 * nodes,
+        };
+        let graph = generate(&model, RandomOptions::new(false, false, false), 0);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let barnes_hut = time_steps(&graph, |graph| {
+            graph.step_layout(&push_conf, &pull_conf, DELTA, &mut rng)
+        });
+        let exact = time_steps(&graph, |graph| {
+            exact_step_layout(graph, &push_conf, &pull_conf, &mut rng)
+        });
+
+        println!(
+            "{:>6} nodes: barnes-hut {:>10.3?}, exact {:>10.3?} per step",
+            nodes, barnes_hut, exact
+        );
+    }
+}
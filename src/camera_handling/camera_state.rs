@@ -4,8 +4,8 @@ use tetra::graphics::Camera;
 use tetra::input::Key;
 use tetra::Context;
 
-use crate::constants::{CAMERA_ZOOM_SPEED, ROTATION_SPEED, X_AXIS_MOVE_SPEED, Y_AXIS_MOVE_SPEED};
-use crate::graph::Position;
+use projekt::constants::{CAMERA_ZOOM_SPEED, ROTATION_SPEED, X_AXIS_MOVE_SPEED, Y_AXIS_MOVE_SPEED};
+use projekt::graph::Position;
 
 pub trait CameraState {
     fn handle_camera_events(&mut self, event: tetra::Event) -> Result<(), Box<dyn Error>>;
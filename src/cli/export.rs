@@ -0,0 +1,104 @@
+use projekt::file_handling::{load_graph, svg, tikz};
+
+use super::run::run_algorithm;
+use super::{parse_node, Args, CliError};
+
+// export <graph file> <output file> [--undirected]
+//        [--algorithm <name> --start <node> [--target <node>] [--step <number>]]
+pub fn export(args: &[String]) -> Result<Vec<String>, CliError> {
+    let args = Args::parse(args, &["--algorithm", "--start", "--target", "--step"])?;
+    args.check_flags(&["--undirected"])?;
+    let (path, output) = match args.positional.as_slice() {
+        [path, output] => (path, output),
+        _ => {
+            return Err(CliError::Usage(String::from(
+                "export needs a graph file and an output file",
+            )))
+        }
+    };
+
+    let undirected = args.flag("--undirected");
+    let (mut graph, directed) = load_graph(path, !undirected)
+        .map_err(|err| CliError::Parse(format!("could not read {}: {}", path, err)))?;
+    let directed = directed && !undirected;
+
+    // The graph is drawn as the algorithm shows it after `--step` steps, or at its end.
+    let algorithm_state = match args.value("--algorithm") {
+        Some(name) => {
+            let start = args
+                .value("--start")
+                .ok_or_else(|| CliError::Usage(String::from("--algorithm needs --start")))?;
+            let start = parse_node(&graph, start, "start")?;
+            let target = args
+                .value("--target")
+                .map(|target| parse_node(&graph, target, "target"))
+                .transpose()?;
+            let step = match args.value("--step") {
+                Some(step) => step
+                    .parse::<usize>()
+                    .map_err(|_| CliError::Usage(format!("--step needs a number, not {}", step)))?,
+                None => usize::MAX,
+            };
+            let mut result = run_algorithm(name, &graph, directed, start, target)?;
+            result.show_algorithm(&mut graph);
+            result.jump_to(&mut graph, step);
+            true
+        }
+        None => false,
+    };
+
+    let result = if output.ends_with(".svg") {
+        svg::save(&graph, directed, output)
+    } else if output.ends_with(".tex") {
+        tikz::save(&graph, directed, algorithm_state, output)
+    } else {
+        return Err(CliError::Usage(format!(
+            "can't tell the format of {}",
+            output
+        )));
+    };
+    result.map_err(|err| CliError::Write(format!("could not write {}: {}", output, err)))?;
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::cli::run_command;
+
+    #[test]
+    fn exports_files() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("projekt_cli_export.txt");
+        let output = dir.join("projekt_cli_export.svg");
+        fs::write(&path, "2\n0 1\n").unwrap();
+        let args = |output: &str| {
+            vec![
+                String::from("export"),
+                String::from(path.to_str().unwrap()),
+                String::from(output),
+            ]
+        };
+
+        assert!(run_command(&args(output.to_str().unwrap()))
+            .unwrap()
+            .is_empty());
+        let svg = fs::read_to_string(&output).unwrap();
+        assert_eq!(svg.matches("<circle").count(), 2);
+
+        let tikz = dir.join("projekt_cli_export.tex");
+        let mut snapshot = args(tikz.to_str().unwrap());
+        snapshot.extend(["--algorithm", "bfs", "--start", "0", "--step", "1"].map(String::from));
+        assert!(run_command(&snapshot).unwrap().is_empty());
+        let tikz = fs::read_to_string(&tikz).unwrap();
+        assert!(tikz.contains("\\begin{tikzpicture}"));
+        // Only the start is queued, the edge is still disabled.
+        assert_eq!(tikz.matches("fill=cc8c8c8").count(), 1);
+        assert!(tikz.contains("draw opacity=0.3"));
+
+        let code = |output: &str| run_command(&args(output)).unwrap_err().exit_code();
+        assert_eq!(code("graph.bmp"), 1);
+        assert_eq!(code("missing/dir/graph.svg"), 5);
+    }
+}
@@ -0,0 +1,168 @@
+use std::error::Error;
+use std::fmt;
+
+use petgraph::graph::NodeIndex;
+
+use projekt::graph::Graph;
+
+mod export;
+mod record;
+mod run;
+
+pub const USAGE: &str = "\
+usage:
+    projekt                   opens the editor
+    projekt run <graph file> <algorithm> <start node> [options]
+                              runs an algorithm without a window and prints its result as JSON
+    projekt export <graph file> <output file> [options]
+                              draws the graph into an .svg file or a TikZ picture in a .tex file
+    projekt record <graph file> <algorithm> <start node> <output directory> [options]
+                              draws the graph after every step of an algorithm into PNG files
+
+algorithms: bfs, dfs, dijkstra, bellman-ford, astar (needs --target),
+            dijkstra and astar refuse negative weights,
+            scc, toposort (kahn), toposort-dfs (directed graphs only),
+            kruskal, prim, biconnectivity (undirected graphs only)
+
+options:
+    --trace                   run: print every step as a JSON line instead of the result
+    --target <node>           where dijkstra, bellman-ford and astar should find the path to
+    --undirected              read the graph as undirected, edge lists are directed otherwise
+    --algorithm <name>        export: draw the graph as the algorithm shows it, needs --start
+    --start <node>            export: where the algorithm starts
+    --step <number>           export: how many steps of the algorithm to show, all by default
+    --size <width>x<height>   record: size of the images, 1280x800 by default
+    --gif <file>              record: also write an animation of all steps
+    --speed <steps/s>         record: speed of the animation
+    --weights                 record: draw the edge weights
+
+exit codes: 1 wrong usage, 2 graph file can't be read, 3 unknown algorithm, 4 invalid node,
+            5 output can't be written, 6 algorithm doesn't run on this graph";
+
+#[derive(Debug)]
+pub enum CliError {
+    Usage(String),
+    Parse(String),
+    UnknownAlgorithm(String),
+    InvalidNode(String),
+    Write(String),
+    // A known algorithm that doesn't apply, like scc on an undirected graph.
+    Unsupported(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 1,
+            CliError::Parse(_) => 2,
+            CliError::UnknownAlgorithm(_) => 3,
+            CliError::InvalidNode(_) => 4,
+            CliError::Write(_) => 5,
+            CliError::Unsupported(_) => 6,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+            CliError::Parse(message)
+            | CliError::UnknownAlgorithm(message)
+            | CliError::InvalidNode(message)
+            | CliError::Write(message)
+            | CliError::Unsupported(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+// Options may come anywhere, everything else is positional.
+struct Args {
+    positional: Vec<String>,
+    flags: Vec<String>,
+    values: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(args: &[String], with_value: &[&str]) -> Result<Args, CliError> {
+        let mut result = Args {
+            positional: Vec::new(),
+            flags: Vec::new(),
+            values: Vec::new(),
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if with_value.contains(&arg.as_str()) {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| CliError::Usage(format!("{} needs a value", arg)))?;
+                result.values.push((arg.clone(), value.clone()));
+            } else if arg.starts_with("--") {
+                result.flags.push(arg.clone());
+            } else {
+                result.positional.push(arg.clone());
+            }
+        }
+        Ok(result)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn check_flags(&self, known: &[&str]) -> Result<(), CliError> {
+        match self
+            .flags
+            .iter()
+            .find(|flag| !known.contains(&flag.as_str()))
+        {
+            Some(flag) => Err(CliError::Usage(format!("unknown option {}", flag))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_node(graph: &Graph, word: &str, what: &str) -> Result<NodeIndex, CliError> {
+    word.parse::<usize>()
+        .ok()
+        .map(NodeIndex::new)
+        .filter(|idx| graph.contains_node(*idx))
+        .ok_or_else(|| CliError::InvalidNode(format!("{} node {} does not exist", what, word)))
+}
+
+// Lines to print on success.
+pub fn run_command(args: &[String]) -> Result<Vec<String>, CliError> {
+    match args.first().map(|command| command.as_str()) {
+        Some("run") => run::run(&args[1..]),
+        Some("export") => export::export(&args[1..]),
+        Some("record") => record::record(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => Ok(vec![String::from(USAGE)]),
+        Some(command) => Err(CliError::Usage(format!("unknown command {}", command))),
+        None => Err(CliError::Usage(String::from("no command given"))),
+    }
+}
+
+// Returns the exit code.
+pub fn run_cli(args: &[String]) -> i32 {
+    match run_command(args) {
+        Ok(lines) => {
+            for line in lines {
+                println!("{}", line);
+            }
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            err.exit_code()
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use projekt::constants::{BASE_PLAYBACK_SPEED, SCREEN_HEIGHT, SCREEN_WIDTH};
+use projekt::file_handling::load_graph;
+use projekt::offscreen::Recording;
+
+use super::run::run_algorithm;
+use super::{parse_node, Args, CliError};
+
+// "1280x800"
+fn parse_size(size: &str) -> Result<(u32, u32), CliError> {
+    size.split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .ok_or_else(|| CliError::Usage(format!("--size needs WIDTHxHEIGHT, not {}", size)))
+}
+
+// record <graph file> <algorithm> <start node> <output directory>
+//        [--target <node>] [--size <width>x<height>] [--gif <file>] [--speed <steps/s>]
+//        [--weights] [--undirected]
+pub fn record(args: &[String]) -> Result<Vec<String>, CliError> {
+    let args = Args::parse(args, &["--target", "--size", "--gif", "--speed"])?;
+    args.check_flags(&["--weights", "--undirected"])?;
+    let (path, name, start, dir) = match args.positional.as_slice() {
+        [path, name, start, dir] => (path, name, start, dir),
+        _ => {
+            return Err(CliError::Usage(String::from(
+                "record needs a graph file, an algorithm, a start node and an output directory",
+            )))
+        }
+    };
+    let size = match args.value("--size") {
+        Some(size) => parse_size(size)?,
+        None => (SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32),
+    };
+    let speed = match args.value("--speed") {
+        Some(speed) => speed
+            .parse::<f32>()
+            .ok()
+            .filter(|speed| *speed > 0.)
+            .ok_or_else(|| CliError::Usage(format!("--speed needs a number, not {}", speed)))?,
+        None => BASE_PLAYBACK_SPEED,
+    };
+
+    let undirected = args.flag("--undirected");
+    let (graph, directed) = load_graph(path, !undirected)
+        .map_err(|err| CliError::Parse(format!("could not read {}: {}", path, err)))?;
+    let directed = directed && !undirected;
+    let start = parse_node(&graph, start, "start")?;
+    let target = args
+        .value("--target")
+        .map(|target| parse_node(&graph, target, "target"))
+        .transpose()?;
+
+    let result = run_algorithm(name, &graph, directed, start, target)?;
+    let mut recording = Recording::new(&graph, directed, result, size.0, size.1);
+    recording
+        .renderer_mut()
+        .set_show_weights(args.flag("--weights"));
+    let count = recording
+        .save(dir, args.value("--gif").map(|gif| (gif, speed)))
+        .map_err(|err| CliError::Write(format!("could not record into {}: {}", dir, err)))?;
+    Ok(vec![format!("{} frames written to {}", count, dir)])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::parse_size;
+    use crate::cli::run_command;
+
+    #[test]
+    fn records_frames() {
+        let dir = std::env::temp_dir().join("projekt_cli_record");
+        let path = dir.with_extension("txt");
+        fs::write(&path, "2\n0 1\n").unwrap();
+        let args = |rest: &[&str]| -> Vec<String> {
+            [
+                "record",
+                path.to_str().unwrap(),
+                "bfs",
+                "0",
+                dir.to_str().unwrap(),
+            ]
+            .iter()
+            .chain(rest)
+            .map(|arg| String::from(*arg))
+            .collect()
+        };
+
+        let lines = run_command(&args(&["--size", "32x24"])).unwrap();
+        assert!(lines[0].starts_with("6 frames"));
+        assert!(dir.join("step_5.png").exists());
+
+        let code = |rest: &[&str]| run_command(&args(rest)).unwrap_err().exit_code();
+        assert_eq!(code(&["--size", "0x10"]), 1);
+        assert_eq!(code(&["--speed", "fast"]), 1);
+        assert_eq!(code(&["--size", "100000x10", "--gif", "run.gif"]), 5);
+        assert_eq!(parse_size("640x480").unwrap(), (640, 480));
+    }
+}
@@ -0,0 +1,240 @@
+use petgraph::graph::NodeIndex;
+use petgraph::Undirected;
+
+use projekt::file_handling::load_graph;
+use projekt::graph::{with_edge_type, Graph};
+use projekt::step_algorithms::{
+    has_negative_weights, AStar, BellmanFord, Bfs, Biconnectivity, Dfs, Dijkstra,
+    DirectedStepAlgorithm, Kruskal, Prim, Scc, SortMethod, StepAlgorithm, StepAlgorithmResult,
+    TopologicalSort, UndirectedStepAlgorithm,
+};
+
+use super::{parse_node, Args, CliError};
+
+// Same as the buttons in the app, undirected graphs are viewed as such by the algorithm.
+fn run_step_algorithm<T: StepAlgorithm>(
+    mut algorithm: T,
+    graph: &Graph,
+    directed: bool,
+    start: NodeIndex,
+) -> StepAlgorithmResult {
+    if directed {
+        algorithm.run(graph, start);
+    } else {
+        algorithm.run(&with_edge_type::<_, _, Undirected>(graph), start);
+    }
+    algorithm.result()
+}
+
+fn run_undirected_algorithm<T: UndirectedStepAlgorithm>(
+    mut algorithm: T,
+    graph: &Graph,
+    start: NodeIndex,
+) -> StepAlgorithmResult {
+    algorithm.run(&with_edge_type(graph), start);
+    algorithm.result()
+}
+
+pub(super) fn run_algorithm(
+    name: &str,
+    graph: &Graph,
+    directed: bool,
+    start: NodeIndex,
+    target: Option<NodeIndex>,
+) -> Result<StepAlgorithmResult, CliError> {
+    match name {
+        "dijkstra" | "astar" if has_negative_weights(graph) => Err(CliError::Unsupported(format!(
+            "{} can't handle negative weights, use bellman-ford",
+            name
+        ))),
+        "bfs" => Ok(run_step_algorithm(
+            Bfs::from_graph(graph),
+            graph,
+            directed,
+            start,
+        )),
+        "dfs" => Ok(run_step_algorithm(
+            Dfs::from_graph(graph),
+            graph,
+            directed,
+            start,
+        )),
+        "dijkstra" => Ok(run_step_algorithm(
+            Dijkstra::new(target),
+            graph,
+            directed,
+            start,
+        )),
+        "bellman-ford" => Ok(run_step_algorithm(
+            BellmanFord::new(target),
+            graph,
+            directed,
+            start,
+        )),
+        "astar" => match target {
+            Some(target) => Ok(run_step_algorithm(
+                AStar::from_graph(graph, target),
+                graph,
+                directed,
+                start,
+            )),
+            None => Err(CliError::Usage(String::from("astar needs --target"))),
+        },
+        "scc" if directed => {
+            let mut scc = Scc::new();
+            scc.run(graph, start);
+            Ok(scc.result())
+        }
+        "scc" => Err(CliError::Unsupported(String::from(
+            "scc only runs on directed graphs",
+        ))),
+        "toposort" | "toposort-dfs" if directed => {
+            let method = match name {
+                "toposort" => SortMethod::Kahn,
+                _ => SortMethod::Dfs,
+            };
+            let mut sort = TopologicalSort::new(method);
+            sort.run(graph, start);
+            Ok(sort.result())
+        }
+        "toposort" | "toposort-dfs" => Err(CliError::Unsupported(format!(
+            "{} only runs on directed graphs",
+            name
+        ))),
+        "kruskal" if !directed => Ok(run_undirected_algorithm(Kruskal::new(), graph, start)),
+        "prim" if !directed => Ok(run_undirected_algorithm(Prim::new(), graph, start)),
+        "biconnectivity" if !directed => Ok(run_undirected_algorithm(
+            Biconnectivity::new(),
+            graph,
+            start,
+        )),
+        "kruskal" | "prim" | "biconnectivity" => Err(CliError::Unsupported(format!(
+            "{} only runs on undirected graphs",
+            name
+        ))),
+        _ => Err(CliError::UnknownAlgorithm(format!(
+            "unknown algorithm {}",
+            name
+        ))),
+    }
+}
+
+// run <graph file> <algorithm> <start node> [--trace] [--target <node>] [--undirected]
+pub fn run(args: &[String]) -> Result<Vec<String>, CliError> {
+    let args = Args::parse(args, &["--target"])?;
+    args.check_flags(&["--trace", "--undirected"])?;
+    let (path, name, start) = match args.positional.as_slice() {
+        [path, name, start] => (path, name, start),
+        _ => {
+            return Err(CliError::Usage(String::from(
+                "run needs a graph file, an algorithm and a start node",
+            )))
+        }
+    };
+
+    let undirected = args.flag("--undirected");
+    let (graph, directed) = load_graph(path, !undirected)
+        .map_err(|err| CliError::Parse(format!("could not read {}: {}", path, err)))?;
+    let directed = directed && !undirected;
+    let start = parse_node(&graph, start, "start")?;
+    let target = args
+        .value("--target")
+        .map(|target| parse_node(&graph, target, "target"))
+        .transpose()?;
+
+    let result = run_algorithm(name, &graph, directed, start, target)?;
+    let lines = if args.flag("--trace") {
+        result
+            .steps()
+            .iter()
+            .map(|step| step.to_json().to_string())
+            .collect()
+    } else {
+        vec![serde_json::to_string(result.outcome()).unwrap()]
+    };
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::cli::run_command;
+
+    fn args(path: &str, rest: &[&str]) -> Vec<String> {
+        ["run", path]
+            .iter()
+            .chain(rest)
+            .map(|arg| String::from(*arg))
+            .collect()
+    }
+
+    #[test]
+    fn results_and_exit_codes() {
+        let path = std::env::temp_dir().join("projekt_cli_test.txt");
+        fs::write(&path, "3\n0 1\n1 2 4\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let lines = run_command(&args(path, &["bfs", "0"])).unwrap();
+        assert_eq!(lines, vec![r#"{"result":"order","order":[0,1,2]}"#]);
+
+        let lines = run_command(&args(
+            path,
+            &["dijkstra", "2", "--undirected", "--target", "0"],
+        ))
+        .unwrap();
+        assert_eq!(
+            lines,
+            vec![r#"{"result":"distances","distances":[[0,5.0],[1,4.0],[2,0.0]],"path":[2,1,0]}"#]
+        );
+
+        let lines = run_command(&args(path, &["bellman-ford", "0", "--undirected"])).unwrap();
+        assert_eq!(
+            lines,
+            vec![r#"{"result":"distances","distances":[[0,0.0],[1,1.0],[2,5.0]],"path":[]}"#]
+        );
+
+        let lines = run_command(&args(path, &["kruskal", "0", "--undirected"])).unwrap();
+        assert_eq!(
+            lines,
+            vec![r#"{"result":"spanning_tree","edges":[0,1],"weight":5.0}"#]
+        );
+
+        let lines = run_command(&args(path, &["biconnectivity", "0", "--undirected"])).unwrap();
+        assert_eq!(
+            lines,
+            vec![r#"{"result":"biconnectivity","articulation_points":[1],"bridges":[0,1]}"#]
+        );
+
+        let trace = run_command(&args(path, &["dfs", "0", "--trace"])).unwrap();
+        assert_eq!(trace.len(), 8);
+        assert_eq!(
+            trace[0],
+            r#"{"idx":0,"step":"dfs_node","to_state":"queued"}"#
+        );
+        assert_eq!(trace[1], r#"{"idx":0,"step":"dfs_edge"}"#);
+
+        let code = |rest: &[&str]| run_command(&args(path, rest)).unwrap_err().exit_code();
+        assert_eq!(code(&["bfs"]), 1);
+        assert_eq!(code(&["bfs", "0", "--fast"]), 1);
+        assert_eq!(code(&["astar", "0"]), 1);
+        assert_eq!(code(&["sort", "0"]), 3);
+        assert_eq!(code(&["scc", "0", "--undirected"]), 6);
+        assert_eq!(code(&["prim", "0"]), 6);
+        assert_eq!(code(&["biconnectivity", "0"]), 6);
+        assert_eq!(code(&["toposort", "0", "--undirected"]), 6);
+        assert_eq!(code(&["bfs", "3"]), 4);
+        assert_eq!(code(&["dijkstra", "0", "--target", "x"]), 4);
+
+        fs::write(path, "3\n0 1 -2\n1 2\n").unwrap();
+        assert_eq!(code(&["dijkstra", "0"]), 6);
+        assert_eq!(code(&["astar", "0", "--target", "2"]), 6);
+        assert!(run_command(&args(path, &["bellman-ford", "0"])).is_ok());
+        assert_eq!(
+            run_command(&args("missing.txt", &["bfs", "0"]))
+                .unwrap_err()
+                .exit_code(),
+            2
+        );
+    }
+}
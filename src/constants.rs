@@ -1,4 +1,4 @@
-use tetra::math::vec::Vec2;
+use vek::Vec2;
 
 pub const CAMERA_ZOOM_SPEED: f32 = 0.05;
 pub const Y_AXIS_MOVE_SPEED: f32 = 7.;
@@ -8,17 +8,33 @@ pub const ROTATION_SPEED: f32 = 0.05;
 pub const BASE_STROKE_WIDTH: f32 = 5.;
 pub const BASE_ARROW_SCALE: f32 = 0.7;
 pub const BASE_ARROW_ARMS_SIZE: f32 = 25.;
+pub const BASE_EDGE_WEIGHT: f32 = 1.;
 
 pub const PUSH_FORCE_FORCE: f32 = 1000.;
 pub const PUSH_FORCE_DISTANCE: f32 = 150.;
+pub const PUSH_FORCE_THETA: f32 = 0.5;
 
 pub const PULL_FORCE_MIN_DISTANCE: f32 = 100.;
 pub const PULL_FORCE_FORCE_AT_TWICE_DISTANCE: f32 = 500.;
 
 pub const BASE_RADIUS: f32 = 20.;
+// From the center of a node to its annotation.
+pub const ANNOTATION_DISTANCE: f32 = BASE_RADIUS * 1.8;
+
+// Algorithm steps shown per second.
+pub const BASE_PLAYBACK_SPEED: f32 = 1. / 0.3;
+pub const MIN_PLAYBACK_SPEED: f32 = 0.5;
+pub const MAX_PLAYBACK_SPEED: f32 = 30.;
+// Seconds nodes take to move into a new arrangement.
+pub const ARRANGE_DURATION: f32 = 1.;
+// Replays list this many steps that do not fit the graph, the rest is only counted.
+pub const MAX_STALE_STEPS_SHOWN: usize = 5;
 pub const BASE_BORDER_SIZE: f32 = 4.;
 pub const HIGHLIGHT_SCALE: Vec2<f32> = Vec2 { x: 1.1, y: 1.1 };
 
+// World units per centimeter in TikZ figures.
+pub const TIKZ_UNITS_PER_CM: f32 = 50.;
+
 pub const SCREEN_WIDTH: f32 = 1280.;
 pub const SCREEN_HEIGHT: f32 = 800.;
 
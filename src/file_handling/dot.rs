@@ -0,0 +1,751 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fmt::Write;
+use std::fs;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use petgraph::graph::NodeIndex;
+
+use crate::constants::BASE_RADIUS;
+use crate::graph::edge::Edge;
+use crate::graph::node::Node;
+use crate::graph::{Color, Graph, Position};
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Id(String),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Equals,
+    Semicolon,
+    Comma,
+    Colon,
+    EdgeOp,
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Lexer<'a> {
+        Lexer {
+            chars: source.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), String> {
+        let mut line_start = self.line == 1;
+        while let Some(&c) = self.chars.peek() {
+            if c == '\n' {
+                line_start = true;
+                self.bump();
+            } else if c.is_whitespace() {
+                self.bump();
+            } else if c == '#' && line_start {
+                // Preprocessor-like lines are comments in DOT.
+                while !matches!(self.bump(), Some('\n') | None) {}
+                line_start = true;
+            } else if c == '/' {
+                let mut lookahead = self.chars.clone();
+                lookahead.next();
+                match lookahead.peek() {
+                    Some('/') => {
+                        while !matches!(self.bump(), Some('\n') | None) {}
+                        line_start = true;
+                    }
+                    Some('*') => {
+                        self.bump();
+                        self.bump();
+                        let mut previous = ' ';
+                        loop {
+                            match self.bump() {
+                                Some('/') if previous == '*' => break,
+                                Some(c) => previous = c,
+                                None => return Err(self.error("unterminated comment")),
+                            }
+                        }
+                    }
+                    _ => return Ok(()),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let mut result = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(result),
+                Some('\\') => match self.bump() {
+                    Some('"') => result.push('"'),
+                    Some('\\') => result.push('\\'),
+                    Some('n') | Some('l') | Some('r') => result.push('\n'),
+                    // Escaped line break continues the string.
+                    Some('\n') => (),
+                    Some(c) => {
+                        result.push('\\');
+                        result.push(c);
+                    }
+                    None => return Err(self.error("unterminated string")),
+                },
+                Some(c) => result.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn html(&mut self) -> Result<String, String> {
+        let mut depth = 1;
+        let mut result = String::new();
+        loop {
+            match self.bump() {
+                Some('<') => depth += 1,
+                Some('>') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(result);
+                    }
+                }
+                Some(c) => result.push(c),
+                None => return Err(self.error("unterminated HTML string")),
+            }
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, usize)>, String> {
+        let mut tokens = Vec::new();
+        loop {
+            self.skip_whitespace_and_comments()?;
+            let line = self.line;
+            let c = match self.bump() {
+                Some(c) => c,
+                None => return Ok(tokens),
+            };
+            let token = match c {
+                '{' => Token::LBrace,
+                '}' => Token::RBrace,
+                '[' => Token::LBracket,
+                ']' => Token::RBracket,
+                '=' => Token::Equals,
+                ';' => Token::Semicolon,
+                ',' => Token::Comma,
+                ':' => Token::Colon,
+                '-' if matches!(self.chars.peek(), Some('>') | Some('-')) => {
+                    self.bump();
+                    Token::EdgeOp
+                }
+                '"' => {
+                    let mut text = self.quoted()?;
+                    // "a" + "b" concatenation.
+                    loop {
+                        self.skip_whitespace_and_comments()?;
+                        if self.chars.peek() != Some(&'+') {
+                            break;
+                        }
+                        self.bump();
+                        self.skip_whitespace_and_comments()?;
+                        if self.bump() != Some('"') {
+                            return Err(self.error("expected string after '+'"));
+                        }
+                        text.push_str(&self.quoted()?);
+                    }
+                    Token::Id(text)
+                }
+                '<' => Token::Id(self.html()?),
+                c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                    let mut text = String::from(c);
+                    while let Some(&next) = self.chars.peek() {
+                        if next.is_alphanumeric() || next == '_' || next == '.' {
+                            text.push(next);
+                            self.bump();
+                        } else {
+                            break;
+                        }
+                    }
+                    Token::Id(text)
+                }
+                c => return Err(self.error(&format!("unexpected character '{}'", c))),
+            };
+            tokens.push((token, line));
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DotNode {
+    name: String,
+    label: Option<String>,
+    position: Option<Position>,
+    color: Option<Color>,
+    border_color: Option<Color>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DotEdge {
+    from: usize,
+    to: usize,
+    color: Option<Color>,
+    label: Option<String>,
+    weight: Option<f32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DotGraph {
+    directed: bool,
+    // Repeated edges are one edge, otherwise every statement adds another.
+    strict: bool,
+    nodes: Vec<DotNode>,
+    edges: Vec<DotEdge>,
+    warnings: Vec<String>,
+}
+
+type Attributes = Vec<(String, String)>;
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    cursor: usize,
+    graph: DotGraph,
+    node_ids: HashMap<String, usize>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cursor).map(|(token, _)| token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id(id)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.cursor).map(|(token, _)| token.clone());
+        self.cursor += 1;
+        token
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self
+            .tokens
+            .get(self.cursor)
+            .or_else(|| self.tokens.last())
+            .map_or(0, |(_, line)| *line);
+        format!("line {}: {}", line, message)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        if self.peek() == Some(&expected) {
+            self.cursor += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {:?}, found {:?}", expected, self.peek())))
+        }
+    }
+
+    fn id(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Id(_)) => match self.next() {
+                Some(Token::Id(id)) => Ok(id),
+                _ => unreachable!(),
+            },
+            other => Err(self.error(&format!("expected identifier, found {:?}", other))),
+        }
+    }
+
+    fn warn(&mut self, warning: String) {
+        if !self.graph.warnings.contains(&warning) {
+            self.graph.warnings.push(warning);
+        }
+    }
+
+    fn parse_graph(&mut self) -> Result<(), String> {
+        if self.peek_keyword("strict") {
+            self.graph.strict = true;
+            self.cursor += 1;
+        }
+        if self.peek_keyword("digraph") {
+            self.graph.directed = true;
+        } else if self.peek_keyword("graph") {
+            self.graph.directed = false;
+        } else {
+            return Err(self.error("expected 'graph' or 'digraph'"));
+        }
+        self.cursor += 1;
+
+        if matches!(self.peek(), Some(Token::Id(_))) {
+            self.cursor += 1;
+        }
+
+        self.expect(Token::LBrace)?;
+        self.parse_stmt_list(Attributes::new(), Attributes::new())?;
+        self.expect(Token::RBrace)?;
+
+        if self.peek().is_some() {
+            return Err(self.error("unexpected input after the closing brace"));
+        }
+        Ok(())
+    }
+
+    // Returns all nodes mentioned in the list, needed when a subgraph is an edge operand.
+    fn parse_stmt_list(
+        &mut self,
+        mut node_defaults: Attributes,
+        mut edge_defaults: Attributes,
+    ) -> Result<Vec<usize>, String> {
+        let mut mentioned = Vec::new();
+
+        while !matches!(self.peek(), Some(Token::RBrace) | None) {
+            if self.peek_keyword("node") || self.peek_keyword("edge") || self.peek_keyword("graph")
+            {
+                let kind = self.id()?.to_lowercase();
+                let attributes = self.parse_attr_lists()?;
+                match kind.as_str() {
+                    "node" => node_defaults.extend(attributes),
+                    "edge" => edge_defaults.extend(attributes),
+                    _ => self.apply_graph_attributes(&attributes),
+                }
+            } else if matches!(self.peek(), Some(Token::Id(_)))
+                && self.tokens.get(self.cursor + 1).map(|(token, _)| token) == Some(&Token::Equals)
+            {
+                let key = self.id()?;
+                self.expect(Token::Equals)?;
+                let value = self.id()?;
+                self.apply_graph_attributes(&[(key, value)]);
+            } else {
+                let mut operands = vec![self.parse_operand(&node_defaults, &edge_defaults)?];
+                while self.peek() == Some(&Token::EdgeOp) {
+                    self.cursor += 1;
+                    operands.push(self.parse_operand(&node_defaults, &edge_defaults)?);
+                }
+                let mut attributes = edge_defaults.clone();
+                if self.peek() == Some(&Token::LBracket) {
+                    if operands.len() == 1 {
+                        // Defaults were already applied when the node was created.
+                        attributes = self.parse_attr_lists()?;
+                        for idx in &operands[0] {
+                            self.apply_node_attributes(*idx, &attributes);
+                        }
+                    } else {
+                        attributes.extend(self.parse_attr_lists()?);
+                    }
+                }
+                for pair in operands.windows(2) {
+                    for from in &pair[0] {
+                        for to in &pair[1] {
+                            self.add_edge(*from, *to, &attributes);
+                        }
+                    }
+                }
+                operands
+                    .into_iter()
+                    .for_each(|operand| mentioned.extend(operand));
+            }
+
+            if self.peek() == Some(&Token::Semicolon) {
+                self.cursor += 1;
+            }
+        }
+
+        Ok(mentioned)
+    }
+
+    fn parse_operand(
+        &mut self,
+        node_defaults: &Attributes,
+        edge_defaults: &Attributes,
+    ) -> Result<Vec<usize>, String> {
+        if self.peek_keyword("subgraph") || self.peek() == Some(&Token::LBrace) {
+            if self.peek_keyword("subgraph") {
+                self.cursor += 1;
+                if matches!(self.peek(), Some(Token::Id(_))) {
+                    self.cursor += 1;
+                }
+            }
+            self.expect(Token::LBrace)?;
+            let nodes = self.parse_stmt_list(node_defaults.clone(), edge_defaults.clone())?;
+            self.expect(Token::RBrace)?;
+            return Ok(nodes);
+        }
+
+        let name = self.id()?;
+        if self.peek() == Some(&Token::Colon) {
+            self.cursor += 1;
+            self.id()?;
+            if self.peek() == Some(&Token::Colon) {
+                self.cursor += 1;
+                self.id()?;
+            }
+            self.warn(String::from(
+                "node ports are not supported and were ignored",
+            ));
+        }
+
+        let idx = match self.node_ids.get(&name) {
+            Some(idx) => *idx,
+            None => {
+                let idx = self.graph.nodes.len();
+                self.graph.nodes.push(DotNode {
+                    name: name.clone(),
+                    label: None,
+                    position: None,
+                    color: None,
+                    border_color: None,
+                });
+                self.node_ids.insert(name, idx);
+                self.apply_node_attributes(idx, node_defaults);
+                idx
+            }
+        };
+        Ok(vec![idx])
+    }
+
+    fn parse_attr_lists(&mut self) -> Result<Attributes, String> {
+        let mut attributes = Attributes::new();
+        while self.peek() == Some(&Token::LBracket) {
+            self.cursor += 1;
+            while self.peek() != Some(&Token::RBracket) {
+                let key = self.id()?;
+                let value = if self.peek() == Some(&Token::Equals) {
+                    self.cursor += 1;
+                    self.id()?
+                } else {
+                    String::from("true")
+                };
+                attributes.push((key, value));
+                if matches!(self.peek(), Some(Token::Comma) | Some(Token::Semicolon)) {
+                    self.cursor += 1;
+                }
+            }
+            self.expect(Token::RBracket)?;
+        }
+        Ok(attributes)
+    }
+
+    fn parse_color(&mut self, value: &str) -> Option<Color> {
+        let color = match value.to_lowercase().as_str() {
+            "black" => Some(Color::BLACK),
+            "white" => Some(Color::WHITE),
+            "red" => Some(Color::RED),
+            "green" => Some(Color::GREEN),
+            "blue" => Some(Color::BLUE),
+            "gray" | "grey" => Some(Color::rgb8(190, 190, 190)),
+            "yellow" => Some(Color::rgb(1., 1., 0.)),
+            "orange" => Some(Color::rgb8(255, 165, 0)),
+            hex if hex.starts_with('#') => Color::from_hex(hex),
+            _ => None,
+        };
+        if color.is_none() {
+            self.warn(format!("unsupported color '{}' was ignored", value));
+        }
+        color
+    }
+
+    fn parse_position(&mut self, value: &str) -> Option<Position> {
+        let mut coordinates = value
+            .trim_end_matches('!')
+            .split(',')
+            .map(|part| part.trim().parse::<f32>());
+        match (coordinates.next(), coordinates.next()) {
+            // Graphviz y axis points up, ours points down.
+            (Some(Ok(x)), Some(Ok(y))) => Some(Position::new(x, -y)),
+            _ => {
+                self.warn(format!("invalid position '{}' was ignored", value));
+                None
+            }
+        }
+    }
+
+    fn apply_node_attributes(&mut self, idx: usize, attributes: &[(String, String)]) {
+        for (key, value) in attributes {
+            match key.as_str() {
+                "label" => self.graph.nodes[idx].label = Some(value.clone()),
+                "pos" => {
+                    if let Some(position) = self.parse_position(value) {
+                        self.graph.nodes[idx].position = Some(position);
+                    }
+                }
+                "fillcolor" => {
+                    if let Some(color) = self.parse_color(value) {
+                        self.graph.nodes[idx].color = Some(color);
+                    }
+                }
+                "color" => {
+                    if let Some(color) = self.parse_color(value) {
+                        self.graph.nodes[idx].border_color = Some(color);
+                    }
+                }
+                // Nodes are always drawn filled.
+                "style" => (),
+                _ => self.warn(format!("unknown node attribute '{}' was ignored", key)),
+            }
+        }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, attributes: &[(String, String)]) {
+        let mut edge = DotEdge {
+            from,
+            to,
+            color: None,
+            label: None,
+            weight: None,
+        };
+        for (key, value) in attributes {
+            match key.as_str() {
+                "color" => edge.color = self.parse_color(value),
+                "label" => edge.label = Some(value.clone()),
+                "weight" => match value.parse::<f32>() {
+                    Ok(weight) if weight.is_finite() => edge.weight = Some(weight),
+                    _ => self.warn(format!("invalid weight '{}' was ignored", value)),
+                },
+                _ => self.warn(format!("unknown edge attribute '{}' was ignored", key)),
+            }
+        }
+        self.graph.edges.push(edge);
+    }
+
+    fn apply_graph_attributes(&mut self, attributes: &[(String, String)]) {
+        for (key, _) in attributes {
+            self.warn(format!("unknown graph attribute '{}' was ignored", key));
+        }
+    }
+}
+
+impl DotGraph {
+    pub fn parse(source: &str) -> Result<DotGraph, Box<dyn Error>> {
+        let mut parser = Parser {
+            tokens: Lexer::new(source).tokenize()?,
+            cursor: 0,
+            graph: DotGraph {
+                directed: true,
+                strict: false,
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                warnings: Vec::new(),
+            },
+            node_ids: HashMap::new(),
+        };
+        parser.parse_graph()?;
+        Ok(parser.graph)
+    }
+
+    pub fn load(path: &str) -> Result<DotGraph, Box<dyn Error>> {
+        DotGraph::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
+    pub fn build_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+
+        // Nodes without a position are spread on a circle, so the forces have something to work with.
+        let ring_radius = BASE_RADIUS * self.nodes.len() as f32 / 2.;
+        for (nr, dot_node) in self.nodes.iter().enumerate() {
+            let position = dot_node.position.unwrap_or_else(|| {
+                Position::up().rotated_z(2. * PI * nr as f32 / self.nodes.len() as f32)
+                    * ring_radius
+            });
+            let mut node = Node::new(position);
+            node.set_node_text(dot_node.label.as_ref().unwrap_or(&dot_node.name));
+            if let Some(color) = dot_node.color {
+                node.set_color(color);
+            }
+            if let Some(color) = dot_node.border_color {
+                node.set_border_color(color);
+            }
+            graph.add_node(node);
+        }
+
+        for dot_edge in &self.edges {
+            let (from, to) = (NodeIndex::new(dot_edge.from), NodeIndex::new(dot_edge.to));
+            // In a strict graph a repeated edge only sets more attributes, `b -- a` repeats `a -- b`.
+            let existing = graph.find_edge(from, to).or_else(|| {
+                if self.directed {
+                    None
+                } else {
+                    graph.find_edge(to, from)
+                }
+            });
+            let idx = match existing {
+                Some(idx) if self.strict => idx,
+                _ => graph.add_edge(from, to, Edge::new()),
+            };
+            let edge = &mut graph[idx];
+            if let Some(color) = dot_edge.color {
+                edge.set_color(color);
+            }
+            if let Some(label) = &dot_edge.label {
+                edge.set_label(label);
+            }
+            if let Some(weight) = dot_edge.weight {
+                edge.set_weight(weight);
+            }
+        }
+
+        graph
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn export(graph: &Graph, directed: bool) -> String {
+    let (keyword, edge_op) = if directed {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+
+    let mut result = format!("{} {{\n", keyword);
+    for idx in graph.node_indices() {
+        if let Some(node) = graph.node_weight(idx) {
+            let position = node.position();
+            writeln!(
+                result,
+                "    {} [label=\"{}\", pos=\"{},{}!\", style=filled, fillcolor=\"{}\", color=\"{}\"];",
+                idx.index(),
+                escape(node.node_text()),
+                position.x,
+                -position.y,
+                node.color().to_hex(),
+                node.border_color().to_hex(),
+            )
+            .unwrap();
+        }
+    }
+    for idx in graph.edge_indices() {
+        if let (Some((from, to)), Some(edge)) = (graph.edge_endpoints(idx), graph.edge_weight(idx))
+        {
+            writeln!(
+                result,
+                "    {} {} {} [label=\"{}\", weight={}, color=\"{}\"];",
+                from.index(),
+                edge_op,
+                to.index(),
+                escape(edge.label()),
+                edge.weight(),
+                edge.color().to_hex()
+            )
+            .unwrap();
+        }
+    }
+    result.push_str("}\n");
+    result
+}
+
+pub fn save(graph: &Graph, directed: bool, path: &str) -> Result<(), Box<dyn Error>> {
+    fs::write(path, export(graph, directed))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::BASE_EDGE_WEIGHT;
+    use crate::graph::Color;
+
+    use super::DotGraph;
+    use crate::graph::Position;
+
+    #[test]
+    fn parses_digraph_with_attributes() {
+        let source = r##"
+            // A comment
+            digraph G {
+                rankdir=LR;
+                a [label="start", pos="10,20!", fillcolor="#ff0000", shape=box];
+                a -> b -> c [color=blue, weight=3, label="road"];
+                /* block
+                   comment */
+                c -> a
+            }
+        "##;
+        let dot = DotGraph::parse(source).unwrap();
+
+        assert!(dot.directed());
+        assert_eq!(dot.nodes.len(), 3);
+        assert_eq!(dot.nodes[0].label, Some(String::from("start")));
+        assert_eq!(dot.nodes[0].position, Some(Position::new(10., -20.)));
+        assert_eq!(dot.nodes[0].color, Some(Color::RED));
+        assert_eq!(dot.nodes[1].name, "b");
+        assert_eq!(
+            dot.edges
+                .iter()
+                .map(|edge| (edge.from, edge.to))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 2), (2, 0)]
+        );
+        assert_eq!(dot.edges[0].color, Some(Color::BLUE));
+        assert_eq!(dot.edges[1].weight, Some(3.));
+        assert_eq!(dot.edges[1].label, Some(String::from("road")));
+        assert_eq!(dot.edges[2].color, None);
+        assert_eq!(dot.edges[2].weight, None);
+        assert_eq!(
+            dot.warnings(),
+            &vec![
+                String::from("unknown graph attribute 'rankdir' was ignored"),
+                String::from("unknown node attribute 'shape' was ignored"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_undirected_subgraph() {
+        let dot = DotGraph::parse("strict graph { 1 -- { 2 3 }; node [label=x]; 4 }").unwrap();
+
+        assert!(!dot.directed());
+        assert_eq!(dot.nodes.len(), 4);
+        assert_eq!(dot.nodes[3].label, Some(String::from("x")));
+        assert_eq!(dot.edges.len(), 2);
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        assert!(DotGraph::parse("digraph { a -> }").is_err());
+        assert!(DotGraph::parse("tree { }").is_err());
+        assert!(DotGraph::parse("graph { a [label=\"oops] }").is_err());
+    }
+
+    #[test]
+    fn repeated_edges_merge_only_when_strict() {
+        let source = "graph { a -- b [weight=2]; b -- a [weight=3]; a -- b }";
+        let graph = DotGraph::parse(source).unwrap().build_graph();
+        let weights = graph
+            .edge_weights()
+            .map(|edge| edge.weight())
+            .collect::<Vec<_>>();
+        assert_eq!(weights, vec![2., 3., BASE_EDGE_WEIGHT]);
+
+        let graph = DotGraph::parse(&format!("strict {}", source))
+            .unwrap()
+            .build_graph();
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.edge_weights().next().unwrap().weight(), 3.);
+    }
+}
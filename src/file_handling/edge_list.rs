@@ -0,0 +1,397 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fmt::Write;
+use std::fs;
+
+use petgraph::graph::NodeIndex;
+use petgraph::stable_graph::StableGraph;
+
+use crate::constants::{BASE_EDGE_WEIGHT, BASE_RADIUS};
+use crate::graph::node::Node;
+use crate::graph::{Graph, GraphOnCanvas, Position};
+
+// Text format: the first line holds the node count, every next line a single "from to" edge,
+// optionally followed by its weight. Nodes are referred to by their index. Removed nodes leave
+// holes, so an index can be bigger than the node count.
+#[derive(Debug, PartialEq)]
+pub struct EdgeList {
+    node_count: usize,
+    edges: Vec<(usize, usize, f32)>,
+    // Line of every edge, errors found by `check` point at it.
+    lines: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LineError {
+    line: usize,
+    message: String,
+}
+
+impl LineError {
+    fn new(line: usize, message: String) -> LineError {
+        LineError { line, message }
+    }
+
+    // Zero based.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+fn parse_index(word: &str) -> Result<usize, String> {
+    word.parse::<usize>()
+        .map_err(|_| format!("'{}' is not a node index", word))
+}
+
+// Drops the nodes with the biggest indices, new nodes go to whatever slots the graph hands out.
+fn resize<N, E>(
+    graph: &mut StableGraph<N, E>,
+    node_count: usize,
+    mut new_node: impl FnMut(&StableGraph<N, E>) -> N,
+) {
+    while graph.node_count() > node_count {
+        if let Some(last) = graph.node_indices().last() {
+            graph.remove_node(last);
+        }
+    }
+    while graph.node_count() < node_count {
+        let node = new_node(graph);
+        graph.add_node(node);
+    }
+}
+
+fn parse_weight(word: &str) -> Result<f32, String> {
+    word.parse::<f32>()
+        .ok()
+        .filter(|weight| weight.is_finite())
+        .ok_or_else(|| format!("'{}' is not a weight", word))
+}
+
+fn to_message(errors: Vec<LineError>) -> String {
+    errors
+        .iter()
+        .map(|error| format!("line {}: {}", error.line() + 1, error.message()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl EdgeList {
+    pub fn from_graph(graph: &Graph) -> EdgeList {
+        let edges = graph
+            .edge_indices()
+            .filter_map(|idx| graph.edge_endpoints(idx).zip(graph.edge_weight(idx)))
+            .map(|((from, to), edge)| (from.index(), to.index(), edge.weight()))
+            .collect::<Vec<_>>();
+        EdgeList {
+            node_count: graph.node_count(),
+            lines: (1..=edges.len()).collect(),
+            edges,
+        }
+    }
+
+    // Numbers nodes in order, skipping the holes, so the list can be loaded into an empty graph.
+    pub fn from_graph_dense(graph: &Graph) -> EdgeList {
+        let positions = graph
+            .node_indices()
+            .enumerate()
+            .map(|(nr, idx)| (idx, nr))
+            .collect::<HashMap<_, _>>();
+        let mut edge_list = EdgeList::from_graph(graph);
+        for (from, to, _) in &mut edge_list.edges {
+            *from = positions[&NodeIndex::new(*from)];
+            *to = positions[&NodeIndex::new(*to)];
+        }
+        edge_list
+    }
+
+    // Only checks the syntax, whether the nodes exist depends on the graph, see `check`.
+    pub fn parse(text: &str) -> Result<EdgeList, Vec<LineError>> {
+        let mut errors = Vec::new();
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let node_count = match lines.next() {
+            None => 0,
+            Some((nr, line)) => match line.trim().parse::<usize>() {
+                Ok(count) => count,
+                Err(_) => {
+                    errors.push(LineError::new(
+                        nr,
+                        String::from("first line should hold the node count"),
+                    ));
+                    0
+                }
+            },
+        };
+
+        let mut edges = Vec::new();
+        let mut edge_lines = Vec::new();
+        for (nr, line) in lines {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            if words.len() != 2 && words.len() != 3 {
+                errors.push(LineError::new(
+                    nr,
+                    String::from("expected two node indices and an optional weight"),
+                ));
+                continue;
+            }
+            let weight = words
+                .get(2)
+                .map_or(Ok(BASE_EDGE_WEIGHT), |word| parse_weight(word));
+            match (parse_index(words[0]), parse_index(words[1]), weight) {
+                (Ok(from), Ok(to), Ok(weight)) => {
+                    edges.push((from, to, weight));
+                    edge_lines.push(nr);
+                }
+                (Err(message), _, _) | (_, Err(message), _) | (_, _, Err(message)) => {
+                    errors.push(LineError::new(nr, message))
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(EdgeList {
+                node_count,
+                edges,
+                lines: edge_lines,
+            })
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Checks that every edge refers to a node that exists once the list is applied to the graph.
+    pub fn check<N, E>(&self, graph: &StableGraph<N, E>) -> Result<(), Vec<LineError>> {
+        // Mapping keeps the vacant slots, so the copy is resized exactly like the graph would be.
+        let mut shape = graph.map(|_, _| (), |_, _| ());
+        resize(&mut shape, self.node_count, |_| ());
+        let existing = shape
+            .node_indices()
+            .map(|idx| idx.index())
+            .collect::<HashSet<_>>();
+
+        let errors = self
+            .edges
+            .iter()
+            .zip(&self.lines)
+            .filter_map(|((from, to, _), line)| {
+                [*from, *to]
+                    .into_iter()
+                    .find(|idx| !existing.contains(idx))
+                    .map(|idx| LineError::new(*line, format!("node {} does not exist", idx)))
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn load(path: &str) -> Result<EdgeList, Box<dyn Error>> {
+        let edge_list = EdgeList::parse(&fs::read_to_string(path)?).map_err(to_message)?;
+        edge_list.check(&Graph::default()).map_err(to_message)?;
+        Ok(edge_list)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut result = format!("{}\n", self.node_count);
+        for (from, to, weight) in &self.edges {
+            // Unweighted graphs keep the plain two column format.
+            if *weight == BASE_EDGE_WEIGHT {
+                writeln!(result, "{} {}", from, to).unwrap();
+            } else {
+                writeln!(result, "{} {} {}", from, to, weight).unwrap();
+            }
+        }
+        result
+    }
+
+    // First weight given for the edge, an undirected edge can be written both ways.
+    fn find_weight(&self, from: usize, to: usize, directed: bool) -> Option<f32> {
+        self.edges
+            .iter()
+            .find(|(a, b, _)| (*a, *b) == (from, to) || (!directed && (*a, *b) == (to, from)))
+            .map(|(_, _, weight)| *weight)
+    }
+
+    // Changes the graph only where it differs from the list, so untouched nodes stay in place.
+    // The list has to pass `check` against the same graph first.
+    pub fn apply(&self, graph: &mut Graph, directed: bool) {
+        let center = if graph.node_count() == 0 {
+            Position::zero()
+        } else {
+            graph
+                .node_weights()
+                .fold(Position::zero(), |sum, node| sum + node.position())
+                / graph.node_count() as f32
+        };
+        resize(graph, self.node_count, |graph| {
+            let offset =
+                Position::up().rotated_z(PI / 3. * graph.node_count() as f32) * BASE_RADIUS * 3.;
+            Node::new(center + offset)
+        });
+
+        let edge_indices = graph.edge_indices().collect::<Vec<_>>();
+        for idx in edge_indices {
+            if let Some((from, to)) = graph.edge_endpoints(idx) {
+                match self.find_weight(from.index(), to.index(), directed) {
+                    Some(weight) => {
+                        if let Some(edge) = graph.edge_weight_mut(idx) {
+                            if edge.weight() != weight {
+                                edge.set_weight(weight);
+                            }
+                        }
+                    }
+                    None => {
+                        graph.remove_edge(idx);
+                    }
+                }
+            }
+        }
+
+        let mut existing = HashSet::new();
+        for (from, to) in graph
+            .edge_indices()
+            .filter_map(|idx| graph.edge_endpoints(idx))
+        {
+            existing.insert((from.index(), to.index()));
+            if !directed {
+                existing.insert((to.index(), from.index()));
+            }
+        }
+        for (from, to, weight) in &self.edges {
+            if existing.insert((*from, *to)) {
+                if !directed {
+                    existing.insert((*to, *from));
+                }
+                let (from, to) = (NodeIndex::new(*from), NodeIndex::new(*to));
+                graph.connect_nodes(from, to);
+                if let Some(edge) = graph
+                    .find_edge(from, to)
+                    .and_then(|idx| graph.edge_weight_mut(idx))
+                {
+                    edge.set_weight(*weight);
+                }
+            }
+        }
+    }
+
+    // Like `apply`, for repeated edits of the same graph. Nodes that are removed and added again
+    // go back to where they were, `positions` remembers them between calls.
+    pub fn apply_keeping_positions(
+        &self,
+        graph: &mut Graph,
+        directed: bool,
+        positions: &mut HashMap<NodeIndex, Position>,
+    ) {
+        let existing = graph.node_indices().collect::<HashSet<_>>();
+        for idx in existing.iter() {
+            positions.insert(*idx, graph[*idx].position());
+        }
+
+        self.apply(graph, directed);
+        for idx in graph.node_indices().collect::<Vec<_>>() {
+            if let (false, Some(position)) = (existing.contains(&idx), positions.get(&idx)) {
+                graph[idx].set_position(*position);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use petgraph::graph::NodeIndex;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::EdgeList;
+    use crate::graph::node::Node;
+    use crate::graph::{Graph, Position};
+
+    #[test]
+    fn parse_and_print() {
+        let text = "3\n0 1\n\n1 2 -2.5\n";
+        let list = EdgeList::parse(text).unwrap();
+        assert_eq!(
+            list,
+            EdgeList {
+                node_count: 3,
+                edges: vec![(0, 1, 1.), (1, 2, -2.5)],
+                lines: vec![1, 3],
+            }
+        );
+        assert_eq!(list.to_text(), "3\n0 1\n1 2 -2.5\n");
+    }
+
+    #[test]
+    fn errors_point_at_lines() {
+        let errors = EdgeList::parse("2\n0 1\n0 x\ny\n1 0\n1 1 w").unwrap_err();
+        assert_eq!(
+            errors.iter().map(|error| error.line()).collect::<Vec<_>>(),
+            vec![2, 3, 5]
+        );
+
+        let errors = EdgeList::parse("2\n0 1\n0 5\n1 0")
+            .unwrap()
+            .check(&StableGraph::<(), ()>::new())
+            .unwrap_err();
+        assert_eq!(
+            errors.iter().map(|error| error.line()).collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn check_follows_index_holes() {
+        let mut graph = StableGraph::<u32, u32>::new();
+        let indices = (0..4).map(|nr| graph.add_node(nr)).collect::<Vec<_>>();
+        graph.remove_node(indices[1]);
+
+        // Nodes 0, 2 and 3 are left.
+        let list = EdgeList::parse("3\n0 3\n2 0").unwrap();
+        assert!(list.check(&graph).is_ok());
+        let list = EdgeList::parse("3\n0 3\n1 0").unwrap();
+        assert_eq!(list.check(&graph).unwrap_err()[0].line(), 2);
+        // A new node fills the hole.
+        let list = EdgeList::parse("4\n1 0").unwrap();
+        assert!(list.check(&graph).is_ok());
+        // Dropping a node removes the biggest index.
+        let list = EdgeList::parse("2\n0 3").unwrap();
+        assert!(list.check(&graph).is_err());
+    }
+
+    #[test]
+    fn removed_nodes_come_back_in_place() {
+        let mut graph = Graph::new();
+        for nr in 0..3 {
+            graph.add_node(Node::new(Position::new(nr as f32 * 100., 0.)));
+        }
+        let mut positions = HashMap::new();
+
+        // Going from 3 to 4 nodes by way of 2, as when the header is retyped.
+        let shrunk = EdgeList::parse("2\n0 1").unwrap();
+        shrunk.apply_keeping_positions(&mut graph, true, &mut positions);
+        assert_eq!(graph.node_count(), 2);
+        let grown = EdgeList::parse("4\n0 1\n2 3").unwrap();
+        grown.apply_keeping_positions(&mut graph, true, &mut positions);
+
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph[NodeIndex::new(2)].position(), Position::new(200., 0.));
+        assert_eq!(graph.edge_count(), 2);
+    }
+}
@@ -0,0 +1,38 @@
+use std::error::Error;
+
+use crate::graph::Graph;
+
+use self::dot::DotGraph;
+use self::edge_list::EdgeList;
+use self::project_file::ProjectFile;
+
+pub mod dot;
+pub mod edge_list;
+pub mod project_file;
+pub mod svg;
+pub mod tikz;
+pub mod trace;
+
+pub fn is_dot_file(path: &str) -> bool {
+    path.ends_with(".dot") || path.ends_with(".gv")
+}
+
+pub fn is_edge_list_file(path: &str) -> bool {
+    path.ends_with(".txt")
+}
+
+// Reads any supported format, picked by extension, and tells whether the graph is directed.
+// Edge lists don't say, `directed` is used for them.
+pub fn load_graph(path: &str, directed: bool) -> Result<(Graph, bool), Box<dyn Error>> {
+    if is_dot_file(path) {
+        let dot_graph = DotGraph::load(path)?;
+        Ok((dot_graph.build_graph(), dot_graph.directed()))
+    } else if is_edge_list_file(path) {
+        let mut graph = Graph::new();
+        EdgeList::load(path)?.apply(&mut graph, directed);
+        Ok((graph, directed))
+    } else {
+        let project = ProjectFile::load(path)?;
+        Ok((project.build_graph(), project.directed()))
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::BASE_EDGE_WEIGHT;
+use crate::graph::gravity::{PullForceConfig, PushForceConfig};
+use crate::graph::node::Node;
+use crate::graph::{Graph, GraphOnCanvas, Position};
+
+// Bump this whenever the layout of the file changes in an incompatible way.
+pub const PROJECT_FILE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct NodeData {
+    position: [f32; 2],
+    text: String,
+}
+
+fn default_weight() -> f32 {
+    BASE_EDGE_WEIGHT
+}
+
+// Edges refer to nodes by their position in the `nodes` vector.
+// Files saved before edges had weights load as unweighted.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EdgeData {
+    from: usize,
+    to: usize,
+    #[serde(default = "default_weight")]
+    weight: f32,
+    #[serde(default)]
+    label: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct CameraData {
+    position: [f32; 2],
+    rotation: f32,
+    scale: [f32; 2],
+}
+
+impl CameraData {
+    pub fn new(position: Position, rotation: f32, scale: Position) -> CameraData {
+        CameraData {
+            position: position.into_array(),
+            rotation,
+            scale: scale.into_array(),
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        Position::from(self.position)
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn scale(&self) -> Position {
+        Position::from(self.scale)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProjectFile {
+    version: u32,
+    directed: bool,
+    push_conf: PushForceConfig,
+    pull_conf: PullForceConfig,
+    camera: CameraData,
+    nodes: Vec<NodeData>,
+    edges: Vec<EdgeData>,
+}
+
+impl ProjectFile {
+    pub fn from_state(
+        graph: &Graph,
+        directed: bool,
+        push_conf: PushForceConfig,
+        pull_conf: PullForceConfig,
+        camera: CameraData,
+    ) -> ProjectFile {
+        let nodes = graph
+            .node_weights()
+            .map(|node| NodeData {
+                position: node.position().into_array(),
+                text: String::from(node.node_text()),
+            })
+            .collect();
+
+        // Removed nodes leave holes in the indices, the file stores nodes densely.
+        let positions = graph
+            .node_indices()
+            .enumerate()
+            .map(|(nr, idx)| (idx, nr))
+            .collect::<HashMap<_, _>>();
+        let edges = graph
+            .edge_indices()
+            .filter_map(|idx| graph.edge_endpoints(idx).zip(graph.edge_weight(idx)))
+            .map(|((from, to), edge)| EdgeData {
+                from: positions[&from],
+                to: positions[&to],
+                weight: edge.weight(),
+                label: String::from(edge.label()),
+            })
+            .collect();
+
+        ProjectFile {
+            version: PROJECT_FILE_VERSION,
+            directed,
+            push_conf,
+            pull_conf,
+            camera,
+            nodes,
+            edges,
+        }
+    }
+
+    pub fn directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn push_conf(&self) -> &PushForceConfig {
+        &self.push_conf
+    }
+
+    pub fn pull_conf(&self) -> &PullForceConfig {
+        &self.pull_conf
+    }
+
+    pub fn camera(&self) -> &CameraData {
+        &self.camera
+    }
+
+    pub fn from_json(json: &str) -> Result<ProjectFile, Box<dyn Error>> {
+        // Version is checked before the rest of the file, so older layouts get a clear error.
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(|version| version.as_u64());
+        if version != Some(PROJECT_FILE_VERSION as u64) {
+            return Err(format!(
+                "unsupported project file version {:?} (expected {})",
+                version, PROJECT_FILE_VERSION
+            )
+            .into());
+        }
+
+        let project: ProjectFile = serde_json::from_value(value)?;
+
+        if let Some(edge) = project
+            .edges
+            .iter()
+            .find(|edge| edge.from >= project.nodes.len() || edge.to >= project.nodes.len())
+        {
+            return Err(format!(
+                "edge {} -> {} refers to a node that does not exist",
+                edge.from, edge.to
+            )
+            .into());
+        }
+
+        Ok(project)
+    }
+
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load(path: &str) -> Result<ProjectFile, Box<dyn Error>> {
+        ProjectFile::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    // Only what the user made is stored, so nodes and edges are rebuilt with their regular constructors.
+    pub fn build_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+
+        for node_data in &self.nodes {
+            let mut node = Node::new(Position::from(node_data.position));
+            node.set_node_text(&node_data.text);
+            graph.add_node(node);
+        }
+
+        for edge_data in &self.edges {
+            let (from, to) = (NodeIndex::new(edge_data.from), NodeIndex::new(edge_data.to));
+            graph.connect_nodes(from, to);
+            if let Some(edge) = graph
+                .find_edge(from, to)
+                .and_then(|idx| graph.edge_weight_mut(idx))
+            {
+                edge.set_label(&edge_data.label);
+                edge.set_weight(edge_data.weight);
+            }
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EdgeData, NodeData, ProjectFile, PROJECT_FILE_VERSION};
+
+    #[test]
+    fn rejects_unknown_version() {
+        let json = r#"{"version": 999}"#;
+        let err = ProjectFile::from_json(json).err().unwrap();
+        assert!(err.to_string().contains("version"));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = format!(
+            r#"{{
+                "version": {},
+                "directed": false,
+                "push_conf": {{ "force": 10.0, "distance": 20.0 }},
+                "pull_conf": {{ "min_distance": 30.0, "force_at_twice_distance": 40.0 }},
+                "camera": {{ "position": [1.0, 2.0], "rotation": 0.5, "scale": [2.0, 2.0] }},
+                "nodes": [
+                    {{ "position": [0.0, 0.0], "text": "a" }},
+                    {{ "position": [100.0, 50.0], "text": "" }}
+                ],
+                "edges": [{{ "from": 0, "to": 1 }}, {{ "from": 1, "to": 0, "weight": 2.5, "label": "back" }}]
+            }}"#,
+            PROJECT_FILE_VERSION
+        );
+
+        let project = ProjectFile::from_json(&json).unwrap();
+        assert!(!project.directed);
+        assert_eq!(project.push_conf.force(), 10.);
+        assert_eq!(project.pull_conf.min_distance(), 30.);
+        assert_eq!(
+            project.nodes[1],
+            NodeData {
+                position: [100., 50.],
+                text: String::new()
+            }
+        );
+        assert_eq!(
+            project.edges,
+            vec![
+                EdgeData {
+                    from: 0,
+                    to: 1,
+                    weight: 1.,
+                    label: String::new()
+                },
+                EdgeData {
+                    from: 1,
+                    to: 0,
+                    weight: 2.5,
+                    label: String::from("back")
+                }
+            ]
+        );
+
+        let reloaded = ProjectFile::from_json(&project.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.nodes, project.nodes);
+        assert_eq!(reloaded.edges, project.edges);
+        assert_eq!(reloaded.camera, project.camera);
+    }
+
+    #[test]
+    fn rejects_dangling_edge() {
+        let json = format!(
+            r#"{{
+                "version": {},
+                "directed": true,
+                "push_conf": {{ "force": 10.0, "distance": 20.0 }},
+                "pull_conf": {{ "min_distance": 30.0, "force_at_twice_distance": 40.0 }},
+                "camera": {{ "position": [0.0, 0.0], "rotation": 0.0, "scale": [1.0, 1.0] }},
+                "nodes": [{{ "position": [0.0, 0.0], "text": "" }}],
+                "edges": [{{ "from": 0, "to": 3 }}]
+            }}"#,
+            PROJECT_FILE_VERSION
+        );
+
+        assert!(ProjectFile::from_json(&json).is_err());
+    }
+}
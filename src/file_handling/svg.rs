@@ -0,0 +1,170 @@
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+
+use crate::constants::{
+    ANNOTATION_DISTANCE, BASE_BORDER_SIZE, BASE_RADIUS, BASE_STROKE_WIDTH, FONT_SIZE,
+};
+use crate::graph::edge::Edge;
+use crate::graph::{drawn_bounds, Color, Graph, GraphOnCanvas, Position};
+
+// Drawn the same way as in the app, in world coordinates. The view box is fitted around the nodes.
+
+fn number(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        String::from("0")
+    } else {
+        String::from(text)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// `attribute` is fill or stroke, disabled edges get their alpha as opacity.
+fn paint(attribute: &str, color: Color) -> String {
+    let mut result = format!("{}=\"{}\"", attribute, color.to_hex());
+    if color.a < 1. {
+        write!(result, " {}-opacity=\"{}\"", attribute, number(color.a)).unwrap();
+    }
+    result
+}
+
+fn write_text(result: &mut String, position: Position, text: &str) {
+    // Text in the app is drawn at FONT_SIZE_SQUARED and scaled down by FONT_SIZE.
+    writeln!(
+        result,
+        "  <text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"monospace\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+        number(position.x),
+        number(position.y),
+        number(FONT_SIZE),
+        escape(text)
+    )
+    .unwrap();
+}
+
+fn write_line(result: &mut String, points: &[Position], color: Color) {
+    let points = points
+        .iter()
+        .map(|point| format!("{},{}", number(point.x), number(point.y)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    writeln!(
+        result,
+        "  <polyline points=\"{}\" fill=\"none\" {} stroke-width=\"{}\"/>",
+        points,
+        paint("stroke", color),
+        number(BASE_STROKE_WIDTH)
+    )
+    .unwrap();
+}
+
+pub fn export(graph: &Graph, directed: bool) -> String {
+    let (min, max) = drawn_bounds(graph).unwrap_or_default();
+    let (min, size) = (min - BASE_RADIUS, max - min + 2. * BASE_RADIUS);
+    let mut result = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n",
+        number(min.x),
+        number(min.y),
+        number(size.x),
+        number(size.y),
+        number(size.x),
+        number(size.y)
+    );
+
+    for idx in graph.edge_indices() {
+        if let (Some((from, to)), Some(edge)) = (graph.edge_line(idx), graph.edge_weight(idx)) {
+            if edge.is_hidden() {
+                continue;
+            }
+            if !directed {
+                write_line(&mut result, &[from, to], edge.color());
+                continue;
+            }
+            let (from, to) = if edge.is_reversed() {
+                (to, from)
+            } else {
+                (from, to)
+            };
+            let (start, tip) = Edge::arrow_line(from, to);
+            let (left, right) = Edge::arrow_arms(from, to);
+            write_line(&mut result, &[start, tip], edge.color());
+            write_line(&mut result, &[left, tip, right], edge.color());
+        }
+    }
+
+    for node in graph.node_weights().filter(|node| !node.is_hidden()) {
+        let position = node.position();
+        writeln!(
+            result,
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" {} {} stroke-width=\"{}\"/>",
+            number(position.x),
+            number(position.y),
+            number(node.radius()),
+            paint("fill", node.color()),
+            paint("stroke", node.border_color()),
+            number(BASE_BORDER_SIZE)
+        )
+        .unwrap();
+        if !node.node_text().is_empty() {
+            write_text(&mut result, position, node.node_text());
+        }
+        if let Some(annotation) = node.annotation() {
+            write_text(
+                &mut result,
+                position - Position::new(0., ANNOTATION_DISTANCE),
+                annotation,
+            );
+        }
+    }
+    result.push_str("</svg>\n");
+    result
+}
+
+pub fn save(graph: &Graph, directed: bool, path: &str) -> Result<(), Box<dyn Error>> {
+    fs::write(path, export(graph, directed))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::export;
+    use crate::graph::node::Node;
+    use crate::graph::{Color, Graph, GraphOnCanvas, Position};
+
+    #[test]
+    fn draws_nodes_and_edges() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(Node::new(Position::new(0., 0.)));
+        let b = graph.add_node(Node::new(Position::new(100., 50.)));
+        graph[a].set_node_text("a<b");
+        graph[b].set_color(Color::GREEN);
+        graph.connect_nodes(a, b);
+
+        let undirected = export(&graph, false);
+        // Nodes reach 24 around their centers, the padding adds another 20.
+        assert!(undirected.contains("viewBox=\"-44 -44 188 138\""));
+        assert!(
+            undirected.contains("<polyline points=\"0,0 100,50\" fill=\"none\" stroke=\"#000000\"")
+        );
+        assert!(undirected.contains("cx=\"100\" cy=\"50\" r=\"20\" fill=\"#00ff00\""));
+        assert!(undirected.contains(">a&lt;b</text>"));
+        assert_eq!(undirected.matches("<polyline").count(), 1);
+
+        graph.edge_weights_mut().for_each(|edge| edge.disable());
+        let directed = export(&graph, true);
+        assert_eq!(directed.matches("<polyline").count(), 2);
+        assert!(directed.contains("stroke-opacity=\"0.3\""));
+    }
+
+    #[test]
+    fn empty_graph() {
+        assert!(export(&Graph::new(), true).contains("viewBox=\"-20 -20 40 40\""));
+    }
+}
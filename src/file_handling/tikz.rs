@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+
+use crate::constants::{BASE_BORDER_SIZE, BASE_STROKE_WIDTH, TIKZ_UNITS_PER_CM};
+use crate::graph::{Color, Graph};
+
+// A tikzpicture to \input into a document, tikz has to be loaded there.
+// With `algorithm_state` disabled edges are faded and annotations are drawn, like in the app
+// while an algorithm is shown. Without it the figure has only the graph itself.
+
+fn cm(value: f32) -> String {
+    let text = format!("{:.2}", value / TIKZ_UNITS_PER_CM);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        String::from("0")
+    } else {
+        String::from(text)
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::new();
+    for char in text.chars() {
+        match char {
+            '\\' => result.push_str("\\textbackslash{}"),
+            '~' => result.push_str("\\textasciitilde{}"),
+            '^' => result.push_str("\\textasciicircum{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                result.push('\\');
+                result.push(char);
+            }
+            _ => result.push(char),
+        }
+    }
+    result
+}
+
+fn color_name(color: Color) -> String {
+    format!("c{}", color.to_hex().trim_start_matches('#'))
+}
+
+// Every color used once, in the order they first appear.
+fn define_colors(graph: &Graph) -> String {
+    let mut colors = Vec::new();
+    let node_colors = graph
+        .node_weights()
+        .flat_map(|node| [node.color(), node.border_color()]);
+    for name in node_colors
+        .chain(graph.edge_weights().map(|edge| edge.color()))
+        .map(color_name)
+    {
+        if !colors.contains(&name) {
+            colors.push(name);
+        }
+    }
+    colors
+        .iter()
+        .map(|name| {
+            format!(
+                "  \\definecolor{{{}}}{{HTML}}{{{}}}\n",
+                name,
+                name[1..].to_uppercase()
+            )
+        })
+        .collect()
+}
+
+pub fn export(graph: &Graph, directed: bool, algorithm_state: bool) -> String {
+    let mut result = String::from("\\begin{tikzpicture}\n");
+    result.push_str(&define_colors(graph));
+
+    for idx in graph.node_indices() {
+        let node = &graph[idx];
+        if node.is_hidden() {
+            continue;
+        }
+        let mut options = format!(
+            "circle, inner sep=0, minimum size={}cm, line width={}cm, fill={}, draw={}",
+            cm(2. * node.radius()),
+            cm(BASE_BORDER_SIZE),
+            color_name(node.color()),
+            color_name(node.border_color())
+        );
+        if let Some(annotation) = node.annotation().filter(|_| algorithm_state) {
+            write!(options, ", label=above:{{{}}}", escape(annotation)).unwrap();
+        }
+        // World y grows downwards, TikZ y upwards.
+        writeln!(
+            result,
+            "  \\node[{}] (n{}) at ({}, {}) {{{}}};",
+            options,
+            idx.index(),
+            cm(node.position().x),
+            cm(-node.position().y),
+            escape(node.node_text())
+        )
+        .unwrap();
+    }
+
+    for idx in graph.edge_indices() {
+        if let (Some((from, to)), Some(edge)) = (graph.edge_endpoints(idx), graph.edge_weight(idx))
+        {
+            if edge.is_hidden() {
+                continue;
+            }
+            let (from, to) = if directed && edge.is_reversed() {
+                (to, from)
+            } else {
+                (from, to)
+            };
+            let mut options = format!(
+                "{}line width={}cm, draw={}",
+                if directed { "->, " } else { "" },
+                cm(BASE_STROKE_WIDTH),
+                color_name(edge.color())
+            );
+            if algorithm_state && edge.color().a < 1. {
+                write!(options, ", draw opacity={}", edge.color().a).unwrap();
+            }
+            writeln!(
+                result,
+                "  \\draw[{}] (n{}) -- (n{});",
+                options,
+                from.index(),
+                to.index()
+            )
+            .unwrap();
+        }
+    }
+    result.push_str("\\end{tikzpicture}\n");
+    result
+}
+
+pub fn save(
+    graph: &Graph,
+    directed: bool,
+    algorithm_state: bool,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    fs::write(path, export(graph, directed, algorithm_state))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, export};
+    use crate::graph::node::Node;
+    use crate::graph::{Color, Graph, GraphOnCanvas, Position};
+
+    #[test]
+    fn draws_nodes_and_edges() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(Node::new(Position::new(0., 0.)));
+        let b = graph.add_node(Node::new(Position::new(100., 50.)));
+        graph[a].set_node_text("a_1");
+        graph[b].set_color(Color::rgb8(255, 165, 0));
+        graph[b].set_annotation(Some(String::from("4")));
+        graph.connect_nodes(a, b);
+        graph.edge_weights_mut().for_each(|edge| edge.disable());
+
+        let plain = export(&graph, false, false);
+        assert_eq!(plain.matches("\\definecolor").count(), 3);
+        assert!(plain.contains("\\definecolor{cffa500}{HTML}{FFA500}"));
+        assert!(plain.contains("fill=cffa500, draw=c000000] (n1) at (2, -1) {};"));
+        assert!(plain.contains("(n0) at (0, 0) {a\\_1};"));
+        assert!(plain.contains("\\draw[line width=0.1cm, draw=c000000] (n0) -- (n1);"));
+        assert!(!plain.contains("label") && !plain.contains("opacity"));
+
+        let state = export(&graph, true, true);
+        assert!(state.contains("label=above:{4}"));
+        assert!(state.contains(
+            "\\draw[->, line width=0.1cm, draw=c000000, draw opacity=0.3] (n0) -- (n1);"
+        ));
+    }
+
+    #[test]
+    fn escapes_latex() {
+        assert_eq!(escape("50% {x}"), "50\\% \\{x\\}");
+        assert_eq!(escape("a\\b~"), "a\\textbackslash{}b\\textasciitilde{}");
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::step_algorithms::{step_from_json, Step, StepAlgorithmResult};
+
+// One JSON object per line, the same as `projekt run --trace` prints.
+// Steps refer to nodes and edges by index, so a trace only fits the graph it was recorded on.
+
+// Where the run of an algorithm on the graph in `graph_path` is kept, e.g. graph.trace.jsonl.
+pub fn trace_path(graph_path: &str) -> String {
+    Path::new(graph_path)
+        .with_extension("trace.jsonl")
+        .to_string_lossy()
+        .into_owned()
+}
+
+pub fn export(steps: &VecDeque<Box<dyn Step>>) -> String {
+    steps
+        .iter()
+        .map(|step| step.to_json().to_string() + "\n")
+        .collect()
+}
+
+pub fn parse(text: &str) -> Result<StepAlgorithmResult, String> {
+    let mut steps = VecDeque::<Box<dyn Step>>::new();
+    for (nr, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let step = serde_json::from_str(line)
+            .map_err(|err| err.to_string())
+            .and_then(step_from_json)
+            .map_err(|err| format!("line {}: {}", nr + 1, err))?;
+        steps.push_back(step);
+    }
+    Ok(StepAlgorithmResult::from_steps(steps))
+}
+
+pub fn save(result: &StepAlgorithmResult, path: &str) -> Result<(), Box<dyn Error>> {
+    fs::write(path, export(result.steps()))?;
+    Ok(())
+}
+
+pub fn load(path: &str) -> Result<StepAlgorithmResult, Box<dyn Error>> {
+    Ok(parse(&fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, trace_path};
+
+    #[test]
+    fn parses_lines() {
+        let result = parse(
+            "{\"step\":\"bfs_node\",\"idx\":0,\"to_state\":\"queued\"}\n\n{\"step\":\"bfs_edge\",\"idx\":1}\n",
+        )
+        .unwrap();
+        assert_eq!(result.steps().len(), 2);
+        assert_eq!(super::export(result.steps()).lines().count(), 2);
+
+        let err = parse("{\"step\":\"bfs_edge\",\"idx\":1}\n{\"step\":\"bfs_edge\"}")
+            .err()
+            .unwrap();
+        assert!(err.starts_with("line 2: bfs_edge step"));
+        assert!(parse("not json").err().unwrap().starts_with("line 1: "));
+    }
+
+    #[test]
+    fn trace_goes_next_to_graph() {
+        assert_eq!(trace_path("graph.json"), "graph.trace.jsonl");
+        assert_eq!(trace_path("dir/graph.dot"), "dir/graph.trace.jsonl");
+    }
+}
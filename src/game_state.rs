@@ -1,40 +1,79 @@
 use std::error::Error;
+use std::path::Path;
+
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 use egui_tetra::egui;
 use egui_tetra::egui::CtxRef;
 use tetra::graphics::scaling::{ScalingMode, ScreenScaler};
-use tetra::graphics::text::Font;
-use tetra::graphics::{self, Camera, Color, FilterMode};
+use tetra::graphics::{self, Camera, Color};
 
-use tetra::input::MouseButton;
+use tetra::input::{self, Key, MouseButton};
 use tetra::Context;
 
 use crate::camera_handling::camera_state::CameraState;
-use crate::graph::{Graph, GraphOnCanvas};
 use crate::input::input_state::{InputState, StateData};
-use crate::step_algorithms::StepAlgorithmResult;
-use crate::tetra_handling::tetra_object::{TetraObject, TetraObjectInfo};
+use crate::tetra_handling::renderer::Renderer;
+use crate::tetra_handling::tetra_object::TetraObjectInfo;
 use crate::ui::ui_drawing::create_ui;
 use crate::ui::ui_state::UiData;
+use projekt::file_handling::dot::{self, DotGraph};
+use projekt::file_handling::edge_list::EdgeList;
+use projekt::file_handling::project_file::{CameraData, ProjectFile};
+use projekt::file_handling::{is_dot_file, is_edge_list_file};
+use projekt::file_handling::{svg, tikz, trace};
+use projekt::graph::generators::insert_graph;
+use projekt::graph::layered::Arrangement;
+use projekt::graph::{Graph, GraphOnCanvas, Position};
+use projekt::history::{Command, EditHistory};
+use projekt::step_algorithms::{grow, StepAlgorithmResult};
 
-use crate::constants::{FONT_SIZE_SQUARED, SCREEN_HEIGHT, SCREEN_WIDTH};
+use projekt::constants::{MAX_STALE_STEPS_SHOWN, SCREEN_HEIGHT, SCREEN_WIDTH};
 
 pub enum AppMode {
     Write,
     Normal,
 }
 
+// What is being written into and its text from before, recorded once writing ends.
+enum TextEdit {
+    Node(NodeIndex, String),
+    Edge(EdgeIndex, String),
+}
+
+fn edit_text(ctx: &mut Context, text: &mut String, max_len: usize) {
+    if let Some(new_input) = input::get_text_input(ctx) {
+        if text.len() <= max_len {
+            text.push_str(new_input);
+        }
+    }
+
+    if input::is_key_pressed(ctx, Key::Backspace) {
+        text.pop();
+    }
+}
+
 pub struct GameState {
     pub graph: Graph,
     pub input_state: InputState,
 
     scaler: ScreenScaler,
 
-    font: Font,
+    renderer: Renderer,
 
     tetra_info: TetraObjectInfo,
 
     algorithm: Option<StepAlgorithmResult>,
+
+    history: EditHistory,
+    text_edit: Option<TextEdit>,
+    // Pushes apart nodes that are in the same place, reseeded with every generated graph.
+    layout_rng: StdRng,
+    // Where nodes go once the algorithm has finished, and the nodes on their way there.
+    pending_arrangement: Option<Vec<(NodeIndex, Position)>>,
+    arrangement: Option<Arrangement>,
 }
 
 impl GameState {
@@ -55,26 +94,335 @@ impl GameState {
             )
             .unwrap(),
             algorithm: None,
-            font: {
-                let mut font = Font::vector(
-                    ctx,
-                    "resources/fonts/JetBrainsMono-Regular.ttf",
-                    FONT_SIZE_SQUARED,
-                )
-                .unwrap();
-                font.set_filter_mode(ctx, FilterMode::Linear);
-                font
-            },
+            history: EditHistory::new(),
+            text_edit: None,
+            renderer: Renderer::new(ctx),
+            layout_rng: StdRng::from_entropy(),
+            pending_arrangement: None,
+            arrangement: None,
         }
     }
 
     pub fn add_algorithm(&mut self, mut algorithm_res: StepAlgorithmResult) {
+        algorithm_res.set_speed(self.tetra_info.ui_data().playback_speed());
         algorithm_res.show_algorithm(&mut self.graph);
-        self.algorithm = Some(algorithm_res);
+        self.set_algorithm(algorithm_res);
+    }
+
+    // Replaces the shown algorithm along with what was waiting for the old one to finish.
+    fn set_algorithm(&mut self, algorithm: StepAlgorithmResult) {
+        self.algorithm = Some(algorithm);
+        self.pending_arrangement = None;
+        self.arrangement = None;
+        // Belongs to the A* run, if that is the one being added it is set again afterwards.
+        *self.tetra_info.ui_data_mut().expanded_counts_mut() = None;
+    }
+
+    // Moves nodes to `targets` once the current algorithm has been played to the end.
+    pub fn arrange_when_finished(&mut self, targets: Vec<(NodeIndex, Position)>) {
+        self.pending_arrangement = Some(targets);
+    }
+
+    // Playback controls change the graph together with the algorithm.
+    pub fn algorithm_mut(&mut self) -> Option<(&mut StepAlgorithmResult, &mut Graph)> {
+        self.algorithm
+            .as_mut()
+            .map(|algorithm| (algorithm, &mut self.graph))
+    }
+
+    pub fn save_project(&mut self) {
+        let path = String::from(self.tetra_info.ui_data().project_path());
+        let result = if is_dot_file(&path) {
+            dot::save(&self.graph, self.tetra_info.ui_data().directed(), &path)
+        } else if is_edge_list_file(&path) {
+            EdgeList::from_graph_dense(&self.graph).save(&path)
+        } else {
+            let (ui_data, camera) = (self.tetra_info.ui_data(), self.tetra_info.camera());
+            ProjectFile::from_state(
+                &self.graph,
+                ui_data.directed(),
+                *ui_data.push_conf(),
+                *ui_data.pull_conf(),
+                CameraData::new(camera.position, camera.rotation, camera.scale),
+            )
+            .save(&path)
+        };
+        let message = match result {
+            Ok(()) => format!("Saved to {}", path),
+            Err(err) => format!("Could not save {}: {}", path, err),
+        };
+        self.tetra_info
+            .ui_data_mut()
+            .set_status_message(Some(message));
+    }
+
+    pub fn load_project(&mut self) {
+        let path = String::from(self.tetra_info.ui_data().project_path());
+        let message = if is_dot_file(&path) {
+            match DotGraph::load(&path) {
+                Ok(dot_graph) => {
+                    self.replace_graph(dot_graph.build_graph());
+                    *self.tetra_info.ui_data_mut().directed_mut() = dot_graph.directed();
+                    let mut message = format!("Imported {}", path);
+                    for warning in dot_graph.warnings() {
+                        message.push_str("\nwarning: ");
+                        message.push_str(warning);
+                    }
+                    message
+                }
+                Err(err) => format!("Could not import {}: {}", path, err),
+            }
+        } else if is_edge_list_file(&path) {
+            match EdgeList::load(&path) {
+                Ok(edge_list) => {
+                    let mut graph = Graph::new();
+                    edge_list.apply(&mut graph, self.tetra_info.ui_data().directed());
+                    self.replace_graph(graph);
+                    format!("Imported {}", path)
+                }
+                Err(err) => format!("Could not import {}: {}", path, err),
+            }
+        } else {
+            match ProjectFile::load(&path) {
+                Ok(project) => {
+                    self.replace_graph(project.build_graph());
+                    self.apply_settings(&project);
+                    format!("Opened {}", path)
+                }
+                Err(err) => format!("Could not open {}: {}", path, err),
+            }
+        };
+        self.tetra_info
+            .ui_data_mut()
+            .set_status_message(Some(message));
+    }
+
+    // Next to the file in the file box, with the extension of the format.
+    fn export_path(&self, extension: &str) -> String {
+        Path::new(self.tetra_info.ui_data().project_path())
+            .with_extension(extension)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn set_export_message(&mut self, path: &str, result: Result<(), Box<dyn Error>>) {
+        let message = match result {
+            Ok(()) => format!("Exported {}", path),
+            Err(err) => format!("Could not export {}: {}", path, err),
+        };
+        self.tetra_info
+            .ui_data_mut()
+            .set_status_message(Some(message));
+    }
+
+    pub fn export_svg(&mut self) {
+        let path = self.export_path("svg");
+        let result = svg::save(&self.graph, self.tetra_info.ui_data().directed(), &path);
+        self.set_export_message(&path, result);
+    }
+
+    pub fn export_tikz(&mut self) {
+        let path = self.export_path("tex");
+        let ui_data = self.tetra_info.ui_data();
+        let algorithm_state = ui_data.export_algorithm_state();
+        let result = match &self.algorithm {
+            Some(algorithm) if !algorithm_state => tikz::save(
+                &algorithm.rewound(&self.graph),
+                ui_data.directed(),
+                false,
+                &path,
+            ),
+            _ => tikz::save(&self.graph, ui_data.directed(), algorithm_state, &path),
+        };
+        self.set_export_message(&path, result);
+    }
+
+    pub fn save_run(&mut self) {
+        let path = trace::trace_path(self.tetra_info.ui_data().project_path());
+        let message = match &self.algorithm {
+            Some(algorithm) => match trace::save(algorithm, &path) {
+                Ok(()) => format!("Saved the run to {}", path),
+                Err(err) => format!("Could not save {}: {}", path, err),
+            },
+            None => String::from("No run to save"),
+        };
+        self.tetra_info
+            .ui_data_mut()
+            .set_status_message(Some(message));
+    }
+
+    // Only layout may change between saving and replaying, steps on missing nodes or edges are refused.
+    pub fn replay_run(&mut self) {
+        let path = trace::trace_path(self.tetra_info.ui_data().project_path());
+        let message = match trace::load(&path) {
+            Ok(algorithm) => {
+                let stale = algorithm.stale_steps(&self.graph);
+                if stale.is_empty() {
+                    self.add_algorithm(algorithm);
+                    format!("Replaying {}", path)
+                } else {
+                    let mut message = format!(
+                        "{} of {} steps in {} don't fit the graph",
+                        stale.len(),
+                        algorithm.steps().len(),
+                        path
+                    );
+                    for step in stale.iter().take(MAX_STALE_STEPS_SHOWN) {
+                        message.push('\n');
+                        message.push_str(&step.to_string());
+                    }
+                    message
+                }
+            }
+            Err(err) => format!("Could not replay {}: {}", path, err),
+        };
+        self.tetra_info
+            .ui_data_mut()
+            .set_status_message(Some(message));
+    }
+
+    fn apply_settings(&mut self, project: &ProjectFile) {
+        let ui_data = self.tetra_info.ui_data_mut();
+        *ui_data.directed_mut() = project.directed();
+        *ui_data.push_conf_mut() = *project.push_conf();
+        *ui_data.pull_conf_mut() = *project.pull_conf();
+
+        let camera: &mut Camera = self.tetra_info.camera_mut();
+        camera.position = project.camera().position();
+        camera.rotation = project.camera().rotation();
+        camera.scale = project.camera().scale();
+        camera.update();
+    }
+
+    fn replace_graph(&mut self, graph: Graph) {
+        self.graph = graph;
+        // Old indices are meaningless in the new graph.
+        self.algorithm = None;
+        self.pending_arrangement = None;
+        self.arrangement = None;
+        self.input_state = InputState::Move(StateData::default());
+        self.history.clear();
+        self.text_edit = None;
     }
 
-    pub fn font(&self) -> Font {
-        self.font.clone()
+    pub fn history(&self) -> &EditHistory {
+        &self.history
+    }
+
+    pub fn push_command(&mut self, command: Command) {
+        self.history.push(command);
+    }
+
+    // Either replaces the graph with a generated one or adds it next to the existing nodes, as one
+    // step to undo. Animated, the new nodes and edges appear one by one like algorithm steps.
+    pub fn add_generated(&mut self, generated: Graph, insert: bool) {
+        let (after, added) = if insert {
+            let mut graph = self.graph.clone();
+            let added = insert_graph(&mut graph, &generated);
+            (graph, added)
+        } else {
+            let added = generated.node_indices().collect();
+            (generated, added)
+        };
+        let before = std::mem::replace(&mut self.graph, after.clone());
+        // Redo shows the whole graph at once.
+        self.push_command(Command::ReplaceGraph { before, after });
+        if !insert {
+            // Like `replace_graph`, old indices are meaningless in the new graph.
+            self.algorithm = None;
+            self.pending_arrangement = None;
+            self.arrangement = None;
+        }
+
+        if self.tetra_info.ui_data().animate_generation() {
+            let mut growth = grow(&mut self.graph, &added);
+            growth.set_speed(self.tetra_info.ui_data().playback_speed());
+            // Unlike algorithms, edges that are already there keep pulling.
+            growth.play();
+            self.set_algorithm(growth);
+        }
+    }
+
+    // Random graphs are laid out the same way every time they are generated from `seed`.
+    pub fn seed_layout(&mut self, seed: u64) {
+        self.layout_rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn undo(&mut self) {
+        self.finish_text_edit();
+        self.input_state.clear_selection(&mut self.graph);
+        self.history.undo(&mut self.graph);
+    }
+
+    pub fn redo(&mut self) {
+        self.finish_text_edit();
+        self.input_state.clear_selection(&mut self.graph);
+        self.history.redo(&mut self.graph);
+    }
+
+    fn finish_text_edit(&mut self) {
+        match self.text_edit.take() {
+            Some(TextEdit::Node(idx, before)) => {
+                if let Some(node) = self.graph.node_weight(idx) {
+                    if node.node_text() != before {
+                        let after = String::from(node.node_text());
+                        self.history.push(Command::EditText { idx, before, after });
+                    }
+                }
+            }
+            Some(TextEdit::Edge(idx, before)) => {
+                if let (Some((from, to)), Some(edge)) =
+                    (self.graph.edge_endpoints(idx), self.graph.edge_weight(idx))
+                {
+                    if edge.text() != before {
+                        let after = String::from(edge.text());
+                        self.history.push(Command::EditEdgeText {
+                            from,
+                            to,
+                            before,
+                            after,
+                        });
+                    }
+                }
+            }
+            None => (),
+        }
+    }
+
+    // Typing goes into the node or edge that writing was started on, Enter ends it.
+    fn text_input(&mut self, ctx: &mut Context) {
+        match &self.text_edit {
+            Some(TextEdit::Node(idx, _)) => {
+                if let Some(node) = self.graph.node_weight_mut(*idx) {
+                    let mut text = String::from(node.node_text());
+                    edit_text(ctx, &mut text, 10);
+                    node.set_node_text(&text);
+                }
+            }
+            Some(TextEdit::Edge(idx, _)) => {
+                if let Some(edge) = self.graph.edge_weight_mut(*idx) {
+                    let mut text = String::from(edge.text());
+                    edit_text(ctx, &mut text, 15);
+                    if text != edge.text() {
+                        edge.set_text(&text);
+                    }
+                }
+            }
+            None => (),
+        }
+
+        if input::is_key_pressed(ctx, Key::Enter) {
+            *self.tetra_info.mode_mut() = AppMode::Normal;
+        }
+    }
+
+    // Edge texts can only be pointed at while they are shown.
+    fn edge_text_from_point(&self, point: Position) -> Option<EdgeIndex> {
+        if self.tetra_info.ui_data().show_weights() {
+            self.graph.edge_text_from_point(point)
+        } else {
+            None
+        }
     }
 
     pub fn tetra_info(&self) -> &TetraObjectInfo {
@@ -93,11 +441,50 @@ impl egui_tetra::State<Box<dyn Error>> for GameState {
         Ok(())
     }
 
-    fn update(&mut self, ctx: &mut Context, _egui_ctx: &CtxRef) -> Result<(), Box<dyn Error>> {
-        self.graph.update(ctx, &mut self.tetra_info);
+    fn update(&mut self, ctx: &mut Context, egui_ctx: &CtxRef) -> Result<(), Box<dyn Error>> {
+        let delta = tetra::time::get_delta_time(ctx).as_secs_f32();
+        self.graph.step_layout(
+            self.tetra_info.ui_data().push_conf(),
+            self.tetra_info.ui_data().pull_conf(),
+            delta,
+            &mut self.layout_rng,
+        );
+
+        if let AppMode::Write = self.tetra_info.mode() {
+            self.text_input(ctx);
+        }
+
+        if let AppMode::Normal = self.tetra_info.mode() {
+            self.finish_text_edit();
+
+            let ctrl_down =
+                input::is_key_down(ctx, Key::LeftCtrl) || input::is_key_down(ctx, Key::RightCtrl);
+            let shift_down =
+                input::is_key_down(ctx, Key::LeftShift) || input::is_key_down(ctx, Key::RightShift);
+            // Text fields have their own undo.
+            if ctrl_down && input::is_key_pressed(ctx, Key::Z) && !egui_ctx.wants_keyboard_input() {
+                if shift_down {
+                    self.redo();
+                } else {
+                    self.undo();
+                }
+            }
+        }
 
         if let Some(alg) = &mut self.algorithm {
-            alg.update(ctx, &mut self.graph);
+            alg.update(delta, &mut self.graph);
+            if alg.is_finished() {
+                if let Some(targets) = self.pending_arrangement.take() {
+                    self.arrangement = Some(Arrangement::new(&self.graph, targets));
+                }
+            }
+        }
+        if let Some(arrangement) = &mut self.arrangement {
+            if arrangement.update(&mut self.graph, delta) {
+                let moves = arrangement.moves().clone();
+                self.arrangement = None;
+                self.push_command(Command::Arrange { moves });
+            }
         }
 
         if let AppMode::Normal = self.tetra_info.mode() {
@@ -113,7 +500,7 @@ impl egui_tetra::State<Box<dyn Error>> for GameState {
         graphics::clear(ctx, Color::rgb(0.392, 0.584, 0.929));
         graphics::set_transform_matrix(ctx, self.tetra_info.camera().as_matrix());
 
-        self.graph.draw(ctx, &mut self.tetra_info);
+        self.renderer.draw(ctx, &self.graph, &self.tetra_info);
 
         graphics::reset_transform_matrix(ctx);
 
@@ -130,7 +517,6 @@ impl egui_tetra::State<Box<dyn Error>> for GameState {
     ) -> Result<(), Box<dyn Error>> {
         if let tetra::Event::MouseMoved { .. } = &event {
             self.input_state.on_mouse_drag(
-                ctx,
                 &mut self.graph,
                 self.tetra_info.camera().mouse_position(ctx),
             );
@@ -140,31 +526,44 @@ impl egui_tetra::State<Box<dyn Error>> for GameState {
             button: MouseButton::Left,
         } = &event
         {
-            self.input_state.on_left_click(
-                ctx,
+            // Steps of the shown algorithm still refer to nodes the user removed.
+            let reserved = self
+                .algorithm
+                .as_ref()
+                .map(|algorithm| algorithm.referenced_nodes())
+                .unwrap_or_default();
+            if let Some(command) = self.input_state.on_left_click(
                 &mut self.graph,
                 self.tetra_info.camera().mouse_position(ctx),
-                self.font.clone(),
-            );
+                &reserved,
+            ) {
+                self.history.push(command);
+            }
         }
 
         if let tetra::Event::MouseButtonPressed {
             button: MouseButton::Right,
         } = &event
         {
-            if self
-                .graph
-                .node_from_point(self.tetra_info.camera().mouse_position(ctx))
-                .is_some()
-            {
+            let mouse_position = self.tetra_info.camera().mouse_position(ctx);
+            if let Some(idx) = self.graph.node_from_point(mouse_position) {
+                self.finish_text_edit();
+                if let Some(node) = self.graph.node_weight(idx) {
+                    self.text_edit = Some(TextEdit::Node(idx, String::from(node.node_text())));
+                }
+                *self.tetra_info.mode_mut() = AppMode::Write;
+            } else if let Some(idx) = self.edge_text_from_point(mouse_position) {
+                self.finish_text_edit();
+                if let Some(edge) = self.graph.edge_weight(idx) {
+                    self.text_edit = Some(TextEdit::Edge(idx, String::from(edge.text())));
+                }
                 *self.tetra_info.mode_mut() = AppMode::Write;
             }
         }
 
-        if self
-            .graph
-            .node_from_point(self.tetra_info.camera().mouse_position(ctx))
-            .is_none()
+        let mouse_position = self.tetra_info.camera().mouse_position(ctx);
+        if self.graph.node_from_point(mouse_position).is_none()
+            && self.edge_text_from_point(mouse_position).is_none()
         {
             *self.tetra_info.mode_mut() = AppMode::Normal;
         }
@@ -0,0 +1,221 @@
+use rand::Rng;
+
+use super::gravity::PushForceConfig;
+use super::Position;
+
+// Past this depth nodes share a leaf, otherwise nodes in the same spot would split cells forever.
+const MAX_DEPTH: u32 = 16;
+
+// Square cell of the quadtree. Leaves hold their nodes, inner cells only the summary.
+struct Cell {
+    center: Position,
+    half_size: f32,
+    mass_center: Position,
+    count: usize,
+    children: Vec<Cell>,
+    points: Vec<(usize, Position)>,
+}
+
+impl Cell {
+    fn new(center: Position, half_size: f32) -> Cell {
+        Cell {
+            center,
+            half_size,
+            mass_center: Position::zero(),
+            count: 0,
+            children: Vec::new(),
+            points: Vec::new(),
+        }
+    }
+
+    fn child_index(&self, position: Position) -> usize {
+        (position.x >= self.center.x) as usize + 2 * (position.y >= self.center.y) as usize
+    }
+
+    fn subdivide(&mut self) {
+        let quarter = self.half_size / 2.;
+        self.children = (0..4)
+            .map(|nr| {
+                let offset = Position::new(
+                    if nr % 2 == 1 { quarter } else { -quarter },
+                    if nr / 2 == 1 { quarter } else { -quarter },
+                );
+                Cell::new(self.center + offset, quarter)
+            })
+            .collect();
+        for (nr, position) in std::mem::take(&mut self.points) {
+            let child = self.child_index(position);
+            self.children[child].points.push((nr, position));
+            self.children[child].mass_center += position;
+            self.children[child].count += 1;
+        }
+        for child in &mut self.children {
+            if child.count > 0 {
+                child.mass_center /= child.count as f32;
+            }
+        }
+    }
+
+    fn insert(&mut self, nr: usize, position: Position, depth: u32) {
+        self.mass_center =
+            (self.mass_center * self.count as f32 + position) / (self.count + 1) as f32;
+        self.count += 1;
+
+        if self.children.is_empty() {
+            if self.points.is_empty() || depth >= MAX_DEPTH {
+                self.points.push((nr, position));
+                return;
+            }
+            self.subdivide();
+        }
+        let child = self.child_index(position);
+        self.children[child].insert(nr, position, depth + 1);
+    }
+
+    // Distance from the point to the closest point of the cell.
+    fn distance_to(&self, position: Position) -> f32 {
+        let offset = (position - self.center).map(|coord| (coord.abs() - self.half_size).max(0.));
+        offset.magnitude()
+    }
+
+    fn push_force(
+        &self,
+        nr: usize,
+        position: Position,
+        conf: &PushForceConfig,
+        rng: &mut impl Rng,
+    ) -> Position {
+        // Nothing in the cell is close enough to push.
+        if self.count == 0 || self.distance_to(position) >= conf.distance() {
+            return Position::zero();
+        }
+
+        if self.children.is_empty() {
+            return self
+                .points
+                .iter()
+                .filter(|(other_nr, _)| *other_nr != nr)
+                .map(|(_, other)| conf.force_between(position, *other, rng))
+                .sum();
+        }
+
+        let distance = position.distance(self.mass_center);
+        let inside = self.distance_to(position) == 0.;
+        if !inside && self.half_size * 2. < conf.theta() * distance {
+            return conf.force_between(position, self.mass_center, rng) * self.count as f32;
+        }
+
+        self.children
+            .iter()
+            .map(|child| child.push_force(nr, position, conf, rng))
+            .sum()
+    }
+}
+
+// Push force acting on every node, in the order of `positions`.
+pub fn push_forces(
+    positions: &[Position],
+    conf: &PushForceConfig,
+    rng: &mut impl Rng,
+) -> Vec<Position> {
+    let (min, max) = match positions.first() {
+        Some(first) => positions
+            .iter()
+            .fold((*first, *first), |(min, max), position| {
+                (
+                    Position::partial_min(min, *position),
+                    Position::partial_max(max, *position),
+                )
+            }),
+        None => return Vec::new(),
+    };
+    let half_size = ((max - min).reduce_partial_max() / 2.).max(1.);
+    let mut root = Cell::new((min + max) / 2., half_size);
+    for (nr, position) in positions.iter().enumerate() {
+        root.insert(nr, *position, 0);
+    }
+
+    positions
+        .iter()
+        .enumerate()
+        .map(|(nr, position)| root.push_force(nr, *position, conf, rng))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::push_forces;
+    use crate::graph::gravity::PushForceConfig;
+    use crate::graph::Position;
+
+    // The double loop that Barnes–Hut replaces.
+    fn exact_push_forces(
+        positions: &[Position],
+        conf: &PushForceConfig,
+        rng: &mut impl Rng,
+    ) -> Vec<Position> {
+        positions
+            .iter()
+            .enumerate()
+            .map(|(nr, position)| {
+                positions
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_nr, _)| *other_nr != nr)
+                    .map(|(_, other)| conf.force_between(*position, *other, rng))
+                    .sum()
+            })
+            .collect()
+    }
+
+    // Density similar to what the editor ends up with.
+    fn random_positions(count: usize) -> Vec<Position> {
+        let mut rng = StdRng::seed_from_u64(count as u64);
+        let side = (count as f32).sqrt() * 60.;
+        (0..count)
+            .map(|_| Position::new(rng.gen_range(0. ..side), rng.gen_range(0. ..side)))
+            .collect()
+    }
+
+    fn relative_error(approximate: &[Position], exact: &[Position]) -> f32 {
+        let error: f32 = approximate
+            .iter()
+            .zip(exact)
+            .map(|(a, b)| a.distance(*b))
+            .sum();
+        let total: f32 = exact.iter().map(|force| force.magnitude()).sum();
+        error / total
+    }
+
+    #[test]
+    fn forces_agree_with_exact() {
+        let positions = random_positions(500);
+        let mut conf = PushForceConfig::new(1000., 150.);
+        let mut rng = StdRng::seed_from_u64(0);
+        let exact = exact_push_forces(&positions, &conf, &mut rng);
+
+        let forces = push_forces(&positions, &conf, &mut rng);
+        assert!(relative_error(&forces, &exact) < 0.05);
+
+        *conf.theta_mut() = 0.;
+        let forces = push_forces(&positions, &conf, &mut rng);
+        assert!(relative_error(&forces, &exact) < 1e-4);
+    }
+
+    #[test]
+    fn handles_nodes_in_the_same_spot() {
+        let positions = vec![Position::new(5., 5.); 100];
+        let conf = PushForceConfig::new(1000., 150.);
+        let forces = push_forces(&positions, &conf, &mut StdRng::seed_from_u64(0));
+        assert_eq!(forces.len(), 100);
+        assert!(forces.iter().all(|force| force.magnitude() > 0.));
+        // The directions nodes in the same spot are pushed in only depend on the seed.
+        assert_eq!(
+            forces,
+            push_forces(&positions, &conf, &mut StdRng::seed_from_u64(0))
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+// Same layout as tetra's color, the app converts when drawing.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0., 0., 0.);
+    pub const WHITE: Color = Color::rgb(1., 1., 1.);
+    pub const RED: Color = Color::rgb(1., 0., 0.);
+    pub const GREEN: Color = Color::rgb(0., 1., 0.);
+    pub const BLUE: Color = Color::rgb(0., 0., 1.);
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Color {
+        Color::rgba(r, g, b, 1.)
+    }
+
+    pub fn rgb8(r: u8, g: u8, b: u8) -> Color {
+        Color::rgb(r as f32 / 255., g as f32 / 255., b as f32 / 255.)
+    }
+
+    pub fn with_alpha(self, a: f32) -> Color {
+        Color { a, ..self }
+    }
+
+    // "#rrggbb" or "#rrggbbaa", the hash is optional.
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if hex.len() != 6 && hex.len() != 8 {
+            return None;
+        }
+        let channel = |nr: usize| {
+            hex.get(nr * 2..nr * 2 + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .map(|value| value as f32 / 255.)
+        };
+        let a = if hex.len() == 8 { channel(3)? } else { 1. };
+        Some(Color::rgba(channel(0)?, channel(1)?, channel(2)?, a))
+    }
+
+    // Alpha is left out, it only marks disabled edges.
+    pub fn to_hex(self) -> String {
+        let to_u8 = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            to_u8(self.r),
+            to_u8(self.g),
+            to_u8(self.b)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Color;
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(Color::from_hex("#ff0000"), Some(Color::RED));
+        assert_eq!(Color::from_hex("00ff00"), Some(Color::GREEN));
+        assert_eq!(
+            Color::from_hex("#0000ff80").map(|color| color.a),
+            Some(128. / 255.)
+        );
+        assert_eq!(Color::from_hex("#12345"), None);
+        assert_eq!(Color::from_hex("#ééé"), None);
+        assert_eq!(Color::rgb8(255, 165, 0).to_hex(), "#ffa500");
+    }
+}
@@ -1,73 +1,173 @@
 use std::f32::consts::PI;
 
-use tetra::graphics::mesh::GeometryBuilder;
-use tetra::graphics::{mesh::Mesh, Color, DrawParams};
-use tetra::math::Vec2;
-use tetra::Context;
-
-use super::Position;
-
 use super::gravity::PullForceConfig;
+use super::{Color, Position};
 
-use crate::constants::{BASE_ARROW_ARMS_SIZE, BASE_ARROW_SCALE, BASE_STROKE_WIDTH};
+use crate::constants::{
+    BASE_ARROW_ARMS_SIZE, BASE_ARROW_SCALE, BASE_EDGE_WEIGHT, BASE_RADIUS, BASE_STROKE_WIDTH,
+};
+use crate::step_algorithms::EdgeWeight;
 
-use crate::tetra_handling::tetra_object::{TetraObject, TetraObjectInfo};
+// Everything algorithm steps may change, see `NodeVisuals`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EdgeVisuals {
+    color: Color,
+    enabled: bool,
+    reversed: bool,
+    hidden: bool,
+}
 
 #[derive(Clone)]
 pub struct Edge {
-    from: Position,
-    to: Position,
     color: Color,
     enabled: bool,
     reversed: bool,
+    // Like hidden nodes, not drawn and not pulling.
+    hidden: bool,
+
+    weight: f32,
+    label: String,
+    // What is drawn at the midpoint and edited, "label weight" or just the weight.
+    text: String,
+}
 
-    arrow: Mesh,
-    line: Mesh,
+fn format_text(label: &str, weight: f32) -> String {
+    if label.is_empty() {
+        weight.to_string()
+    } else {
+        format!("{} {}", label, weight)
+    }
+}
+
+fn parse_weight(word: &str) -> Option<f32> {
+    word.parse::<f32>().ok().filter(|weight| weight.is_finite())
+}
+
+// A number at the end of the text is the weight, the rest is the label.
+// Without a number the edge gets the default weight.
+fn parse_text(text: &str) -> (String, f32) {
+    let trimmed = text.trim();
+    if let Some(weight) = parse_weight(trimmed) {
+        return (String::new(), weight);
+    }
+    trimmed
+        .rsplit_once(char::is_whitespace)
+        .and_then(|(label, last)| parse_weight(last).map(|weight| (label, weight)))
+        .map_or(
+            (String::from(trimmed), BASE_EDGE_WEIGHT),
+            |(label, weight)| (String::from(label.trim()), weight),
+        )
 }
 
 impl Edge {
-    fn create_arrow(ctx: &mut Context, from: Position, to: Position) -> Mesh {
-        let (from, to) = (
-            Position::lerp(from, to, (1. - BASE_ARROW_SCALE) / 2.),
-            Position::lerp(from, to, (1. + BASE_ARROW_SCALE) / 2.),
-        );
-        let left_arrow_point =
-            (to - from).rotated_z(PI * 3. / 4.).normalized() * BASE_ARROW_ARMS_SIZE + to;
-        let right_arrow_point =
-            (to - from).rotated_z(-PI * 3. / 4.).normalized() * BASE_ARROW_ARMS_SIZE + to;
-        let mut builder = GeometryBuilder::new();
-
-        builder.polyline(BASE_STROKE_WIDTH, &[from, to]).unwrap();
-        builder
-            .polyline(
-                BASE_STROKE_WIDTH,
-                &[left_arrow_point, to, right_arrow_point],
-            )
-            .unwrap();
-        builder.build_mesh(ctx).unwrap()
-    }
-
-    pub fn new(ctx: &mut Context, from: Position, to: Position) -> Edge {
+    // Endpoints are not stored, they are the positions of the nodes the edge connects.
+    pub fn new() -> Edge {
         Edge {
-            from,
-            to,
             reversed: false,
             color: Color::BLACK,
-            arrow: Edge::create_arrow(ctx, from, to),
-            line: Mesh::polyline(ctx, BASE_STROKE_WIDTH, &[from, to]).unwrap(),
+            weight: BASE_EDGE_WEIGHT,
+            label: String::new(),
+            text: format_text("", BASE_EDGE_WEIGHT),
             enabled: true,
+            hidden: false,
         }
     }
 
-    pub fn update_position(&mut self, ctx: &mut Context, from: Position, to: Position) {
-        self.from = from;
-        self.to = to;
-        if !self.reversed {
-            self.arrow = Edge::create_arrow(ctx, from, to);
-        } else {
-            self.arrow = Edge::create_arrow(ctx, to, from);
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    // Alpha is left alone, it tells whether the edge is enabled.
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color.with_alpha(self.color.a);
+    }
+
+    pub fn visuals(&self) -> EdgeVisuals {
+        EdgeVisuals {
+            color: self.color,
+            enabled: self.enabled,
+            reversed: self.reversed,
+            hidden: self.hidden,
         }
-        self.line = Mesh::polyline(ctx, BASE_STROKE_WIDTH, &[from, to]).unwrap();
+    }
+
+    pub fn set_visuals(&mut self, visuals: EdgeVisuals) {
+        self.color = visuals.color;
+        self.enabled = visuals.enabled;
+        self.reversed = visuals.reversed;
+        self.hidden = visuals.hidden;
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight;
+        self.text = format_text(&self.label, weight);
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn set_label(&mut self, label: &str) {
+        self.label = String::from(label);
+        self.text = format_text(label, self.weight);
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        let (label, weight) = parse_text(text);
+        self.text = String::from(text);
+        self.label = label;
+        self.weight = weight;
+    }
+
+    // Where the text is drawn for an edge going from `from` to `to`.
+    pub fn text_position(from: Position, to: Position) -> Position {
+        Position::lerp(from, to, 0.5)
+    }
+
+    // Directed edges are drawn shorter, so the arrow heads don't hide under the nodes.
+    // `from` and `to` are already swapped for reversed edges.
+    pub fn arrow_line(from: Position, to: Position) -> (Position, Position) {
+        (
+            Position::lerp(from, to, (1. - BASE_ARROW_SCALE) / 2.),
+            Position::lerp(from, to, (1. + BASE_ARROW_SCALE) / 2.),
+        )
+    }
+
+    // Ends of the two arms at the tip of `arrow_line`.
+    pub fn arrow_arms(from: Position, to: Position) -> (Position, Position) {
+        let (from, to) = Edge::arrow_line(from, to);
+        let arm =
+            |angle: f32| to + (to - from).normalized().rotated_z(angle) * BASE_ARROW_ARMS_SIZE;
+        (arm(PI * 3. / 4.), arm(-PI * 3. / 4.))
+    }
+
+    // Is point over the text drawn at the midpoint?
+    pub fn text_contains(from: Position, to: Position, point: Position) -> bool {
+        Position::distance(point, Edge::text_position(from, to)) <= BASE_RADIUS
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
     }
 
     pub fn reverse(&mut self) {
@@ -87,47 +187,43 @@ impl Edge {
     pub fn reset_state(&mut self) {
         self.reversed = false;
         self.enabled = true;
-        self.color.a = 1.0;
-    }
-
-    fn draw_params(&self) -> DrawParams {
-        DrawParams::new()
-            // What is the purpose of this line? After disabling it, the program behaves the same // I still do not know
-            .position(Position::zero())
-            .color(self.color)
+        self.hidden = false;
+        self.color = Color::BLACK;
     }
 
-    pub fn calculate_pull_force(&self, config: &PullForceConfig) -> Position {
-        if !self.enabled {
+    pub fn calculate_pull_force(
+        &self,
+        from: Position,
+        to: Position,
+        config: &PullForceConfig,
+    ) -> Position {
+        if !self.enabled || self.hidden {
             return Position::zero();
         }
 
-        let distance = self.from.distance(self.to);
+        let distance = from.distance(to);
 
         if distance < config.min_distance() {
             Position::zero()
         } else {
-            let direction = (self.to - self.from).normalized();
+            let direction = (to - from).normalized();
             let force_value =
                 (distance / config.min_distance() - 1.) * config.force_at_twice_distance();
             direction * force_value
         }
     }
 
-    pub fn is_point_in_shape(&self, point: Vec2<f32>) -> bool {
+    pub fn is_point_in_shape(from: Position, to: Position, point: Position) -> bool {
         // We have to make sure that the point is between the lines,
         // otherwise it would be possible to remove edge by clicking anywhere along the line (from, to)
         // since triangle area check would yield zero.
-        if !((point.ge(&self.from) && point.le(&self.to))
-            || (point.ge(&self.to) && point.le(&self.from)))
-        {
+        if !((point.ge(&from) && point.le(&to)) || (point.ge(&to) && point.le(&from))) {
             return false;
         }
 
-        let max_triangle_area =
-            Vec2::triangle_area(self.from, self.to, self.from + 1.5 * BASE_STROKE_WIDTH);
+        let max_triangle_area = Position::triangle_area(from, to, from + 1.5 * BASE_STROKE_WIDTH);
 
-        let triangle_area = Vec2::triangle_area(self.from, self.to, point);
+        let triangle_area = Position::triangle_area(from, to, point);
 
         if triangle_area <= max_triangle_area {
             return true;
@@ -137,14 +233,30 @@ impl Edge {
     }
 }
 
-impl TetraObject for Edge {
-    fn draw(&mut self, ctx: &mut Context, info: &mut TetraObjectInfo) {
-        if info.ui_data().directed() {
-            self.arrow.draw(ctx, self.draw_params());
-        } else {
-            self.line.draw(ctx, self.draw_params());
-        }
+impl EdgeWeight for Edge {
+    fn weight(&self) -> f32 {
+        self.weight
+    }
+}
+
+impl Default for Edge {
+    fn default() -> Edge {
+        Edge::new()
     }
+}
 
-    fn update(&mut self, _ctx: &mut Context, _info: &mut TetraObjectInfo) {}
+#[cfg(test)]
+mod tests {
+    use super::{format_text, parse_text};
+
+    #[test]
+    fn weight_is_parsed_from_the_end() {
+        assert_eq!(parse_text("2.5"), (String::new(), 2.5));
+        assert_eq!(parse_text(" road  -3 "), (String::from("road"), -3.));
+        assert_eq!(parse_text("long road 4"), (String::from("long road"), 4.));
+        assert_eq!(parse_text("road inf"), (String::from("road inf"), 1.));
+        assert_eq!(parse_text(""), (String::new(), 1.));
+        assert_eq!(format_text("", 1.), "1");
+        assert_eq!(format_text("road", 2.5), "road 2.5");
+    }
 }
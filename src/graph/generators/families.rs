@@ -0,0 +1,302 @@
+use std::f32::consts::PI;
+
+use super::{build_graph, circle_layout};
+use crate::constants::BASE_RADIUS;
+use crate::graph::{Graph, Position};
+
+// Between neighboring nodes in grids, trees and bipartite graphs.
+const SPACING: f32 = BASE_RADIUS * 5.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NamedGraph {
+    Petersen,
+    Heawood,
+    Octahedron,
+    Dodecahedron,
+}
+
+// Graphs with a known shape. In directed graphs edges go right and down in grids, from parents to
+// children in trees, around cycles, out of the hub of wheels, from the left side of bipartite
+// graphs and towards the bigger index in hypercubes and named graphs.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Family {
+    Grid { rows: u32, cols: u32 },
+    Tree { branching: u32, depth: u32 },
+    Cycle { nodes: u32 },
+    // `rim` nodes in a cycle, all connected to one more in the middle.
+    Wheel { rim: u32 },
+    CompleteBipartite { left: u32, right: u32 },
+    Hypercube { dimension: u32 },
+    Named(NamedGraph),
+}
+
+impl Family {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Family::Grid { .. } => "Grid",
+            Family::Tree { .. } => "Tree",
+            Family::Cycle { .. } => "Cycle",
+            Family::Wheel { .. } => "Wheel",
+            Family::CompleteBipartite { .. } => "Complete bipartite",
+            Family::Hypercube { .. } => "Hypercube",
+            Family::Named(NamedGraph::Petersen) => "Petersen",
+            Family::Named(NamedGraph::Heawood) => "Heawood",
+            Family::Named(NamedGraph::Octahedron) => "Octahedron",
+            Family::Named(NamedGraph::Dodecahedron) => "Dodecahedron",
+        }
+    }
+}
+
+// Nodes on a circle around the origin, starting straight up and `turn` of a step further.
+fn ring(count: usize, radius: f32, turn: f32) -> Vec<Position> {
+    (0..count)
+        .map(|nr| Position::up().rotated_z(2. * PI * (nr as f32 + turn) / count as f32) * radius)
+        .collect()
+}
+
+fn grid(rows: usize, cols: usize) -> (Vec<Position>, Vec<(usize, usize)>) {
+    let corner = Position::new(cols as f32 - 1., rows as f32 - 1.) * SPACING / 2.;
+    let positions = (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| Position::new(col as f32, row as f32)))
+        .map(|position| position * SPACING - corner)
+        .collect();
+    let mut edges = Vec::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            let nr = row * cols + col;
+            if col + 1 < cols {
+                edges.push((nr, nr + 1));
+            }
+            if row + 1 < rows {
+                edges.push((nr, nr + cols));
+            }
+        }
+    }
+    (positions, edges)
+}
+
+// Levels from the root down, leaves evenly spread and every parent above the middle of its children.
+fn tree(branching: usize, depth: usize) -> (Vec<Position>, Vec<(usize, usize)>) {
+    let width = branching.pow(depth as u32) as f32 * SPACING;
+    let mut positions = Vec::new();
+    let mut edges = Vec::new();
+    let mut level_start = 0;
+    for level in 0..=depth {
+        let count = branching.pow(level as u32);
+        for nr in 0..count {
+            let x = (nr as f32 + 0.5) * width / count as f32 - width / 2.;
+            positions.push(Position::new(x, level as f32 * SPACING));
+            if level > 0 {
+                let parent_start = level_start - count / branching;
+                edges.push((parent_start + nr / branching, level_start + nr));
+            }
+        }
+        level_start += count;
+    }
+    let height = depth as f32 * SPACING;
+    for position in &mut positions {
+        position.y -= height / 2.;
+    }
+    (positions, edges)
+}
+
+fn cycle_edges(start: usize, count: usize) -> Vec<(usize, usize)> {
+    match count {
+        0 | 1 => Vec::new(),
+        2 => vec![(start, start + 1)],
+        _ => (0..count)
+            .map(|nr| (start + nr, start + (nr + 1) % count))
+            .collect(),
+    }
+}
+
+fn complete_bipartite(left: usize, right: usize) -> (Vec<Position>, Vec<(usize, usize)>) {
+    let column = |x: f32, count: usize| {
+        (0..count).map(move |nr| Position::new(x, (nr as f32 - (count as f32 - 1.) / 2.) * SPACING))
+    };
+    let positions = column(-SPACING, left)
+        .chain(column(SPACING, right))
+        .collect();
+    let edges = (0..left)
+        .flat_map(|from| (0..right).map(move |to| (from, left + to)))
+        .collect();
+    (positions, edges)
+}
+
+// Every bit moves the node along its own direction, with growing lengths so no two nodes meet.
+fn hypercube(dimension: usize) -> (Vec<Position>, Vec<(usize, usize)>) {
+    let directions = (0..dimension)
+        .map(|bit| {
+            Position::right().rotated_z(PI * bit as f32 / dimension as f32)
+                * SPACING
+                * (1. + bit as f32 / 2.)
+        })
+        .collect::<Vec<_>>();
+    let count = 1 << dimension;
+    let mut positions = (0..count)
+        .map(|nr: usize| {
+            (0..dimension)
+                .filter(|bit| nr & (1 << bit) != 0)
+                .fold(Position::zero(), |sum, bit| sum + directions[bit])
+        })
+        .collect::<Vec<_>>();
+    let center = positions.iter().fold(Position::zero(), |sum, p| sum + *p) / count as f32;
+    for position in &mut positions {
+        *position -= center;
+    }
+    let edges = (0..count)
+        .flat_map(|nr| {
+            (0..dimension)
+                .map(move |bit| (nr, nr | (1 << bit)))
+                .filter(|(from, to)| from != to)
+        })
+        .collect();
+    (positions, edges)
+}
+
+fn named(graph: NamedGraph) -> (Vec<Position>, Vec<(usize, usize)>) {
+    match graph {
+        // A pentagon around a pentagram.
+        NamedGraph::Petersen => {
+            let mut positions = ring(5, 2. * SPACING, 0.);
+            positions.extend(ring(5, SPACING, 0.));
+            let mut edges = cycle_edges(0, 5);
+            edges.extend((0..5).map(|nr| (nr, nr + 5)));
+            edges.extend((0..5).map(|nr| (5 + nr, 5 + (nr + 2) % 5)));
+            (positions, edges)
+        }
+        // A 14-cycle with chords between opposite sides.
+        NamedGraph::Heawood => {
+            let mut edges = cycle_edges(0, 14);
+            edges.extend((0..14).step_by(2).map(|nr| (nr, (nr + 5) % 14)));
+            (ring(14, 2.5 * SPACING, 0.), edges)
+        }
+        // A triangle around an upside down one, every outer node connected to the two inner ones
+        // nearest to it.
+        NamedGraph::Octahedron => {
+            let mut positions = ring(3, 2. * SPACING, 0.);
+            positions.extend(ring(3, 0.5 * SPACING, 0.5));
+            let edges = (0..6)
+                .flat_map(|from| (from + 1..6).map(move |to| (from, to)))
+                .filter(|&(from, to)| from >= 3 || to != 3 + (from + 1) % 3)
+                .collect();
+            (positions, edges)
+        }
+        // Outer pentagon, a ring of ten and inner pentagon.
+        NamedGraph::Dodecahedron => {
+            let mut positions = ring(5, 3. * SPACING, 0.);
+            positions.extend(ring(10, 2. * SPACING, 0.));
+            positions.extend(ring(5, SPACING, 0.5));
+            let mut edges = cycle_edges(0, 5);
+            edges.extend((0..5).map(|nr| (nr, 5 + 2 * nr)));
+            edges.extend(cycle_edges(5, 10));
+            edges.extend((0..5).map(|nr| (6 + 2 * nr, 15 + nr)));
+            edges.extend(cycle_edges(15, 5));
+            (positions, edges)
+        }
+    }
+}
+
+pub fn generate(family: &Family) -> Graph {
+    let (positions, edges) = match *family {
+        Family::Grid { rows, cols } => grid(rows as usize, cols as usize),
+        Family::Tree { branching, depth } => tree(branching as usize, depth as usize),
+        Family::Cycle { nodes } => (
+            circle_layout(nodes as usize),
+            cycle_edges(0, nodes as usize),
+        ),
+        Family::Wheel { rim } => {
+            let mut positions = circle_layout(rim as usize);
+            positions.push(Position::zero());
+            let mut edges = cycle_edges(0, rim as usize);
+            edges.extend((0..rim as usize).map(|nr| (rim as usize, nr)));
+            (positions, edges)
+        }
+        Family::CompleteBipartite { left, right } => {
+            complete_bipartite(left as usize, right as usize)
+        }
+        Family::Hypercube { dimension } => hypercube(dimension as usize),
+        Family::Named(graph) => named(graph),
+    };
+    build_graph(&positions, &edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{generate, Family, NamedGraph};
+    use crate::graph::Graph;
+
+    fn degrees(graph: &Graph) -> Vec<usize> {
+        graph
+            .node_indices()
+            .map(|idx| graph.neighbors_undirected(idx).count())
+            .collect()
+    }
+
+    #[test]
+    fn families_have_known_sizes() {
+        let sizes = |family: Family| {
+            let graph = generate(&family);
+            (graph.node_count(), graph.edge_count())
+        };
+        assert_eq!(sizes(Family::Grid { rows: 5, cols: 5 }), (25, 40));
+        assert_eq!(
+            sizes(Family::Tree {
+                branching: 2,
+                depth: 3
+            }),
+            (15, 14)
+        );
+        assert_eq!(sizes(Family::Cycle { nodes: 7 }), (7, 7));
+        assert_eq!(sizes(Family::Wheel { rim: 6 }), (7, 12));
+        assert_eq!(
+            sizes(Family::CompleteBipartite { left: 3, right: 3 }),
+            (6, 9)
+        );
+        assert_eq!(sizes(Family::Hypercube { dimension: 4 }), (16, 32));
+        assert_eq!(sizes(Family::Cycle { nodes: 1 }), (1, 0));
+        assert_eq!(sizes(Family::Hypercube { dimension: 0 }), (1, 0));
+    }
+
+    #[test]
+    fn named_graphs_are_regular() {
+        for (graph, nodes, degree) in [
+            (NamedGraph::Petersen, 10, 3),
+            (NamedGraph::Heawood, 14, 3),
+            (NamedGraph::Octahedron, 6, 4),
+            (NamedGraph::Dodecahedron, 20, 3),
+        ] {
+            let graph = generate(&Family::Named(graph));
+            assert_eq!(graph.node_count(), nodes);
+            assert!(degrees(&graph).iter().all(|&count| count == degree));
+        }
+    }
+
+    #[test]
+    fn nodes_dont_overlap() {
+        for family in [
+            Family::Grid { rows: 3, cols: 4 },
+            Family::Tree {
+                branching: 3,
+                depth: 2,
+            },
+            Family::Wheel { rim: 5 },
+            Family::Hypercube { dimension: 5 },
+            Family::Named(NamedGraph::Dodecahedron),
+        ] {
+            let graph = generate(&family);
+            let positions = graph
+                .node_weights()
+                .map(|node| {
+                    (
+                        node.position().x.round() as i32,
+                        node.position().y.round() as i32,
+                    )
+                })
+                .collect::<HashSet<_>>();
+            assert_eq!(positions.len(), graph.node_count(), "{}", family.name());
+        }
+    }
+}
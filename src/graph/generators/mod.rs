@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use petgraph::graph::NodeIndex;
+
+use crate::constants::BASE_RADIUS;
+
+use super::edge::Edge;
+use super::node::Node;
+use super::{drawn_bounds, Graph, Position};
+
+pub mod families;
+pub mod random;
+
+// Room for a node and some space around it along a circle.
+const CIRCLE_SPACING: f32 = BASE_RADIUS * 3.;
+
+// `count` positions evenly spread on a circle around the origin, big enough that nodes don't overlap.
+pub fn circle_layout(count: usize) -> Vec<Position> {
+    let radius = (count as f32 * CIRCLE_SPACING / (2. * PI)).max(CIRCLE_SPACING);
+    (0..count)
+        .map(|nr| Position::up().rotated_z(2. * PI * nr as f32 / count as f32) * radius)
+        .collect()
+}
+
+// Nodes are referred to by their position in `positions`.
+pub fn build_graph(positions: &[Position], edges: &[(usize, usize)]) -> Graph {
+    let mut graph = Graph::new();
+    let indices = positions
+        .iter()
+        .map(|position| graph.add_node(Node::new(*position)))
+        .collect::<Vec<_>>();
+    for (from, to) in edges {
+        graph.add_edge(indices[*from], indices[*to], Edge::new());
+    }
+    graph
+}
+
+// Copies `other` into `graph`, moved right of what is already there so the two don't overlap,
+// and returns the indices of the new nodes in the order of `other`.
+pub fn insert_graph(graph: &mut Graph, other: &Graph) -> Vec<NodeIndex> {
+    let shift = match (drawn_bounds(graph), drawn_bounds(other)) {
+        (Some((min, max)), Some((other_min, other_max))) => Position::new(
+            max.x + CIRCLE_SPACING - other_min.x,
+            (min.y + max.y - other_min.y - other_max.y) / 2.,
+        ),
+        _ => Position::zero(),
+    };
+    let mut indices = HashMap::new();
+    let mut added = Vec::new();
+    for idx in other.node_indices() {
+        let mut node = other[idx].clone();
+        node.set_position(node.position() + shift);
+        let new_idx = graph.add_node(node);
+        indices.insert(idx, new_idx);
+        added.push(new_idx);
+    }
+    for idx in other.edge_indices() {
+        let (from, to) = other.edge_endpoints(idx).unwrap();
+        graph.add_edge(indices[&from], indices[&to], other[idx].clone());
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_graph, circle_layout, insert_graph};
+    use crate::graph::drawn_bounds;
+
+    #[test]
+    fn inserted_graph_is_beside() {
+        let mut graph = build_graph(&circle_layout(3), &[(0, 1), (1, 2)]);
+        let other = build_graph(&circle_layout(4), &[(0, 1), (2, 3), (3, 0)]);
+        let (min, max) = drawn_bounds(&graph).unwrap();
+        let added = insert_graph(&mut graph, &other);
+        assert_eq!(added.len(), 4);
+        assert_eq!(graph.node_count(), 7);
+        assert_eq!(graph.edge_count(), 5);
+        assert!(graph
+            .node_weights()
+            .skip(3)
+            .all(|node| node.position().x - node.radius() > max.x));
+        // Both are centered on the same height.
+        let mut inserted = graph.clone();
+        inserted.retain_nodes(|_, idx| added.contains(&idx));
+        let (other_min, other_max) = drawn_bounds(&inserted).unwrap();
+        assert!(((min.y + max.y) - (other_min.y + other_max.y)).abs() < 1e-3);
+    }
+}
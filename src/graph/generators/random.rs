@@ -0,0 +1,396 @@
+use std::collections::HashSet;
+
+use rand::rngs::StdRng;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+
+use super::{build_graph, circle_layout};
+use crate::graph::Graph;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RandomModel {
+    // G(n, p): every pair of nodes is connected with the same probability.
+    Gnp {
+        nodes: u32,
+        probability: f32,
+    },
+    // G(n, m): `edges` edges picked uniformly from all possible ones.
+    Gnm {
+        nodes: u32,
+        edges: u32,
+    },
+    // Barabási–Albert: every new node connects to `edges_per_node` nodes, picked by their degree.
+    PreferentialAttachment {
+        nodes: u32,
+        edges_per_node: u32,
+    },
+    // Watts–Strogatz: a ring where every node reaches `neighbors` nodes on each side, and every
+    // edge is moved to a random node with probability `rewiring`.
+    SmallWorld {
+        nodes: u32,
+        neighbors: u32,
+        rewiring: f32,
+    },
+}
+
+impl RandomModel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RandomModel::Gnp { .. } => "G(n, p)",
+            RandomModel::Gnm { .. } => "G(n, m)",
+            RandomModel::PreferentialAttachment { .. } => "Preferential attachment",
+            RandomModel::SmallWorld { .. } => "Small world",
+        }
+    }
+
+    pub fn nodes(&self) -> u32 {
+        match *self {
+            RandomModel::Gnp { nodes, .. }
+            | RandomModel::Gnm { nodes, .. }
+            | RandomModel::PreferentialAttachment { nodes, .. }
+            | RandomModel::SmallWorld { nodes, .. } => nodes,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct RandomOptions {
+    directed: bool,
+    self_loops: bool,
+    multi_edges: bool,
+}
+
+impl RandomOptions {
+    pub fn new(directed: bool, self_loops: bool, multi_edges: bool) -> RandomOptions {
+        RandomOptions {
+            directed,
+            self_loops,
+            multi_edges,
+        }
+    }
+
+    pub fn directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn self_loops(&self) -> bool {
+        self.self_loops
+    }
+
+    pub fn multi_edges(&self) -> bool {
+        self.multi_edges
+    }
+}
+
+// Edges between nodes numbered from 0, without loops or duplicates unless the options allow them.
+// In undirected graphs a-b and b-a are the same edge.
+struct EdgeSet {
+    options: RandomOptions,
+    seen: HashSet<(usize, usize)>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl EdgeSet {
+    fn new(options: RandomOptions) -> EdgeSet {
+        EdgeSet {
+            options,
+            seen: HashSet::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    fn key(&self, from: usize, to: usize) -> (usize, usize) {
+        if self.options.directed {
+            (from, to)
+        } else {
+            (from.min(to), from.max(to))
+        }
+    }
+
+    fn allows(&self, from: usize, to: usize) -> bool {
+        (from != to || self.options.self_loops)
+            && (self.options.multi_edges || !self.seen.contains(&self.key(from, to)))
+    }
+
+    fn add(&mut self, from: usize, to: usize) -> bool {
+        if !self.allows(from, to) {
+            return false;
+        }
+        self.seen.insert(self.key(from, to));
+        self.edges.push((from, to));
+        true
+    }
+
+    // Points edge number `nr` at `to` instead.
+    fn move_end(&mut self, nr: usize, to: usize) {
+        let (from, old) = self.edges[nr];
+        self.edges[nr] = (from, to);
+        // Only forget the old pair if no other copy of the edge is left.
+        let old_key = self.key(from, old);
+        if !self.edges.iter().any(|&(a, b)| self.key(a, b) == old_key) {
+            self.seen.remove(&old_key);
+        }
+        self.seen.insert(self.key(from, to));
+    }
+}
+
+// Every edge the options allow, each once.
+fn all_pairs(nodes: usize, options: RandomOptions) -> Vec<(usize, usize)> {
+    (0..nodes)
+        .flat_map(|from| (0..nodes).map(move |to| (from, to)))
+        .filter(|&(from, to)| from != to || options.self_loops)
+        .filter(|&(from, to)| options.directed || from <= to)
+        .collect()
+}
+
+fn gnp(nodes: usize, probability: f32, options: RandomOptions, rng: &mut impl Rng) -> EdgeSet {
+    let mut edges = EdgeSet::new(options);
+    for (from, to) in all_pairs(nodes, options) {
+        if rng.gen::<f32>() < probability {
+            edges.add(from, to);
+        }
+    }
+    edges
+}
+
+fn gnm(nodes: usize, count: usize, options: RandomOptions, rng: &mut impl Rng) -> EdgeSet {
+    let mut edges = EdgeSet::new(options);
+    let pairs = all_pairs(nodes, options);
+    if options.multi_edges {
+        if !pairs.is_empty() {
+            for _ in 0..count {
+                let (from, to) = pairs[rng.gen_range(0..pairs.len())];
+                edges.add(from, to);
+            }
+        }
+    } else {
+        // Asking for more edges than there are pairs gives all of them.
+        for (from, to) in pairs.choose_multiple(rng, count) {
+            edges.add(*from, *to);
+        }
+    }
+    edges
+}
+
+// New nodes point at the nodes they attach to in directed graphs.
+fn preferential_attachment(
+    nodes: usize,
+    edges_per_node: usize,
+    options: RandomOptions,
+    rng: &mut impl Rng,
+) -> EdgeSet {
+    let mut edges = EdgeSet::new(options);
+    let edges_per_node = edges_per_node.max(1).min(nodes.saturating_sub(1));
+    if edges_per_node == 0 {
+        return edges;
+    }
+    // Every node once per edge it has, picking from it goes by degree.
+    let mut ends = Vec::new();
+    // The first new node has nothing to choose from and connects to all starting nodes.
+    for target in 0..edges_per_node {
+        edges.add(edges_per_node, target);
+        ends.extend([edges_per_node, target]);
+    }
+    for node in edges_per_node + 1..nodes {
+        let mut targets = Vec::new();
+        while targets.len() < edges_per_node {
+            let target = *ends.choose(rng).unwrap();
+            if options.multi_edges || !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        for target in targets {
+            edges.add(node, target);
+            ends.extend([node, target]);
+        }
+    }
+    edges
+}
+
+// Edges go clockwise around the ring in directed graphs.
+fn small_world(
+    nodes: usize,
+    neighbors: usize,
+    rewiring: f32,
+    options: RandomOptions,
+    rng: &mut impl Rng,
+) -> EdgeSet {
+    let mut edges = EdgeSet::new(options);
+    // More neighbors would reach around the ring and meet the ones from the other side.
+    let neighbors = neighbors.min(nodes.saturating_sub(1) / 2);
+    for distance in 1..=neighbors {
+        for from in 0..nodes {
+            edges.add(from, (from + distance) % nodes);
+        }
+    }
+    // Edges that have nowhere to go stay where they are.
+    for nr in 0..edges.edges.len() {
+        if rng.gen::<f32>() >= rewiring {
+            continue;
+        }
+        let (from, to) = edges.edges[nr];
+        let target = (0..nodes)
+            .filter(|&target| target != to && edges.allows(from, target))
+            .choose(rng);
+        if let Some(target) = target {
+            edges.move_end(nr, target);
+        }
+    }
+    edges
+}
+
+fn generate_edges(
+    model: &RandomModel,
+    options: RandomOptions,
+    rng: &mut impl Rng,
+) -> Vec<(usize, usize)> {
+    let edges = match *model {
+        RandomModel::Gnp { nodes, probability } => gnp(nodes as usize, probability, options, rng),
+        RandomModel::Gnm { nodes, edges } => gnm(nodes as usize, edges as usize, options, rng),
+        RandomModel::PreferentialAttachment {
+            nodes,
+            edges_per_node,
+        } => preferential_attachment(nodes as usize, edges_per_node as usize, options, rng),
+        RandomModel::SmallWorld {
+            nodes,
+            neighbors,
+            rewiring,
+        } => small_world(nodes as usize, neighbors as usize, rewiring, options, rng),
+    };
+    edges.edges
+}
+
+// The same seed always gives the same graph.
+pub fn generate(model: &RandomModel, options: RandomOptions, seed: u64) -> Graph {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let edges = generate_edges(model, options, &mut rng);
+    build_graph(&circle_layout(model.nodes() as usize), &edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{generate, generate_edges, RandomModel, RandomOptions};
+    use crate::graph::Graph;
+
+    fn check_simple(edges: &[(usize, usize)], directed: bool) {
+        let mut seen = HashSet::new();
+        for &(from, to) in edges {
+            assert_ne!(from, to);
+            let key = if directed {
+                (from, to)
+            } else {
+                (from.min(to), from.max(to))
+            };
+            assert!(seen.insert(key), "{:?} twice", key);
+        }
+    }
+
+    #[test]
+    fn models_make_simple_graphs() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for directed in [false, true] {
+            let options = RandomOptions::new(directed, false, false);
+            let models = [
+                RandomModel::Gnp {
+                    nodes: 30,
+                    probability: 0.3,
+                },
+                RandomModel::Gnm {
+                    nodes: 30,
+                    edges: 100,
+                },
+                RandomModel::PreferentialAttachment {
+                    nodes: 30,
+                    edges_per_node: 3,
+                },
+                RandomModel::SmallWorld {
+                    nodes: 30,
+                    neighbors: 2,
+                    rewiring: 0.5,
+                },
+            ];
+            let counts = models.map(|model| {
+                let edges = generate_edges(&model, options, &mut rng);
+                check_simple(&edges, directed);
+                assert!(edges.iter().all(|&(from, to)| from < 30 && to < 30));
+                edges.len()
+            });
+            assert!(counts[0] > 0);
+            assert_eq!(counts[1], 100);
+            // The first attaching node and every later one bring three edges each.
+            assert_eq!(counts[2], 3 * 27);
+            assert_eq!(counts[3], 60);
+        }
+    }
+
+    #[test]
+    fn options_are_respected() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let complete = RandomModel::Gnp {
+            nodes: 5,
+            probability: 1.,
+        };
+        let count = |model, options| generate_edges(&model, options, &mut rng.clone()).len();
+        assert_eq!(count(complete, RandomOptions::new(false, false, false)), 10);
+        assert_eq!(count(complete, RandomOptions::new(true, false, false)), 20);
+        assert_eq!(count(complete, RandomOptions::new(true, true, false)), 25);
+
+        // Only so many edges fit without duplicates.
+        let dense = RandomModel::Gnm {
+            nodes: 4,
+            edges: 50,
+        };
+        assert_eq!(count(dense, RandomOptions::new(false, false, false)), 6);
+        assert_eq!(count(dense, RandomOptions::new(false, false, true)), 50);
+        let edges = generate_edges(&dense, RandomOptions::new(false, true, true), &mut rng);
+        assert!(edges.iter().any(|(from, to)| from == to));
+
+        // Without rewiring the small world is a plain ring.
+        let ring = RandomModel::SmallWorld {
+            nodes: 6,
+            neighbors: 1,
+            rewiring: 0.,
+        };
+        let edges = generate_edges(&ring, RandomOptions::new(true, false, false), &mut rng);
+        assert_eq!(edges, vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    }
+
+    #[test]
+    fn same_seed_same_graph() {
+        let edges = |graph: &Graph| {
+            graph
+                .edge_indices()
+                .map(|idx| graph.edge_endpoints(idx).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let positions = |graph: &Graph| {
+            graph
+                .node_weights()
+                .map(|node| node.position())
+                .collect::<Vec<_>>()
+        };
+        let options = RandomOptions::new(false, false, false);
+        for model in [
+            RandomModel::Gnp {
+                nodes: 20,
+                probability: 0.3,
+            },
+            RandomModel::SmallWorld {
+                nodes: 20,
+                neighbors: 2,
+                rewiring: 0.5,
+            },
+        ] {
+            let graph = generate(&model, options, 42);
+            let again = generate(&model, options, 42);
+            assert_eq!(edges(&graph), edges(&again));
+            assert_eq!(positions(&graph), positions(&again));
+            assert_ne!(edges(&graph), edges(&generate(&model, options, 43)));
+        }
+    }
+}
@@ -1,6 +1,16 @@
 use std::borrow::BorrowMut;
 
-#[derive(Clone, Copy)]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::Position;
+use crate::constants::PUSH_FORCE_THETA;
+
+fn default_theta() -> f32 {
+    PUSH_FORCE_THETA
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct PullForceConfig {
     min_distance: f32,
     force_at_twice_distance: f32,
@@ -31,15 +41,52 @@ impl PullForceConfig {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct PushForceConfig {
     force: f32,
     distance: f32,
+    // Barnes–Hut accuracy, groups of nodes that look smaller than theta are pushed by as one.
+    // Zero is exact.
+    #[serde(default = "default_theta")]
+    theta: f32,
 }
 
 impl PushForceConfig {
     pub fn new(force: f32, distance: f32) -> PushForceConfig {
-        PushForceConfig { force, distance }
+        PushForceConfig {
+            force,
+            distance,
+            theta: PUSH_FORCE_THETA,
+        }
+    }
+
+    // Force pushing a node at `position` away from a node at `other`, zero from `distance` on.
+    // Nodes in the same place are pushed in a direction drawn from `rng`.
+    pub fn force_between(
+        &self,
+        position: Position,
+        other: Position,
+        rng: &mut impl Rng,
+    ) -> Position {
+        let mut direction_to = position - other;
+        if direction_to.is_approx_zero() {
+            direction_to = Position::up().rotated_z(rng.gen::<f32>());
+        }
+        let force_div = 1. - position.distance(other) / self.distance;
+
+        if force_div <= 0. {
+            return Position::zero();
+        }
+
+        direction_to.normalized() * self.force * force_div
+    }
+
+    pub fn theta(&self) -> f32 {
+        self.theta
+    }
+
+    pub fn theta_mut(&mut self) -> &mut f32 {
+        self.theta.borrow_mut()
     }
 
     pub fn force(&self) -> f32 {
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use petgraph::graph::NodeIndex;
+use petgraph::Direction;
+
+use crate::constants::{ARRANGE_DURATION, BASE_RADIUS};
+
+use super::{Graph, Position};
+
+const LAYER_SPACING: f32 = BASE_RADIUS * 6.;
+const ROW_SPACING: f32 = BASE_RADIUS * 4.;
+
+// Lays out nodes in topological `order` from left to right. Every node goes one layer past the
+// furthest node it has an edge from, and a layer keeps the order top to bottom. The layers are
+// centered where the nodes are now.
+pub fn layered_positions(graph: &Graph, order: &[NodeIndex]) -> Vec<(NodeIndex, Position)> {
+    let mut layers: HashMap<NodeIndex, usize> = HashMap::new();
+    let mut layer_sizes: Vec<usize> = Vec::new();
+    for idx in order {
+        let layer = graph
+            .neighbors_directed(*idx, Direction::Incoming)
+            .filter_map(|other| layers.get(&other))
+            .map(|layer| layer + 1)
+            .max()
+            .unwrap_or(0);
+        layers.insert(*idx, layer);
+        if layer_sizes.len() <= layer {
+            layer_sizes.resize(layer + 1, 0);
+        }
+        layer_sizes[layer] += 1;
+    }
+
+    let positions = order
+        .iter()
+        .filter_map(|idx| graph.node_weight(*idx).map(|node| node.position()))
+        .collect::<Vec<_>>();
+    let center = positions.iter().copied().sum::<Position>() / positions.len().max(1) as f32;
+    let width = layer_sizes.len().saturating_sub(1) as f32 * LAYER_SPACING;
+
+    let mut placed = vec![0; layer_sizes.len()];
+    order
+        .iter()
+        .map(|idx| {
+            let layer = layers[idx];
+            let height = (layer_sizes[layer] - 1) as f32 * ROW_SPACING;
+            let offset = Position::new(
+                layer as f32 * LAYER_SPACING - width / 2.,
+                placed[layer] as f32 * ROW_SPACING - height / 2.,
+            );
+            placed[layer] += 1;
+            (*idx, center + offset)
+        })
+        .collect()
+}
+
+// Moves nodes to new positions over `ARRANGE_DURATION` seconds. Moving nodes ignore the layout
+// forces, and stay where they end up until the algorithm state is reset or the move is undone.
+pub struct Arrangement {
+    moves: Vec<(NodeIndex, Position, Position)>,
+    elapsed: f32,
+}
+
+impl Arrangement {
+    pub fn new(graph: &Graph, targets: Vec<(NodeIndex, Position)>) -> Arrangement {
+        let moves = targets
+            .into_iter()
+            .filter_map(|(idx, to)| {
+                graph
+                    .node_weight(idx)
+                    .map(|node| (idx, node.position(), to))
+            })
+            .collect();
+        Arrangement { moves, elapsed: 0. }
+    }
+
+    // Every node with where it started and where it goes.
+    pub fn moves(&self) -> &Vec<(NodeIndex, Position, Position)> {
+        &self.moves
+    }
+
+    // True once every node has arrived.
+    pub fn update(&mut self, graph: &mut Graph, delta: f32) -> bool {
+        self.elapsed += delta;
+        let progress = (self.elapsed / ARRANGE_DURATION).min(1.);
+        // Starts and stops smoothly.
+        let eased = progress * progress * (3. - 2. * progress);
+        for (idx, from, to) in &self.moves {
+            if let Some(node) = graph.node_weight_mut(*idx) {
+                node.set_ignore_force(true);
+                node.set_position(*from + (*to - *from) * eased);
+            }
+        }
+        progress >= 1.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+
+    use super::{layered_positions, Arrangement, LAYER_SPACING, ROW_SPACING};
+    use crate::graph::node::Node;
+    use crate::graph::{Graph, GraphOnCanvas, Position};
+
+    #[test]
+    fn layers_follow_the_longest_path() {
+        let mut graph = Graph::new();
+        let nodes = (0..4)
+            .map(|nr| graph.add_node(Node::new(Position::new(nr as f32 * 10., 0.))))
+            .collect::<Vec<_>>();
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3), (0, 3)] {
+            graph.connect_nodes(nodes[from], nodes[to]);
+        }
+
+        let positions = layered_positions(&graph, &nodes);
+        let center = Position::new(15., 0.);
+        assert_eq!(
+            positions,
+            vec![
+                (nodes[0], center + Position::new(-LAYER_SPACING, 0.)),
+                (nodes[1], center + Position::new(0., -ROW_SPACING / 2.)),
+                (nodes[2], center + Position::new(0., ROW_SPACING / 2.)),
+                (nodes[3], center + Position::new(LAYER_SPACING, 0.)),
+            ]
+        );
+    }
+
+    #[test]
+    fn arrangement_ends_in_place() {
+        let mut graph = Graph::new();
+        let idx = graph.add_node(Node::new(Position::zero()));
+        let target = Position::new(100., 50.);
+        let mut arrangement =
+            Arrangement::new(&graph, vec![(idx, target), (NodeIndex::new(5), target)]);
+
+        assert!(!arrangement.update(&mut graph, 0.01));
+        assert!(graph[idx].position() != target);
+        assert!(arrangement.update(&mut graph, 10.));
+        assert_eq!(graph[idx].position(), target);
+        assert_eq!(arrangement.moves().len(), 1);
+    }
+}
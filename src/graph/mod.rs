@@ -1,10 +1,15 @@
 use petgraph::{
-    graph::NodeIndex,
+    graph::{EdgeIndex, NodeIndex},
+    stable_graph::StableGraph,
+    visit::{EdgeIndexable, EdgeRef},
     Directed,
     EdgeDirection::{Incoming, Outgoing},
+    EdgeType,
 };
-use tetra::Context;
-use tetra::{graphics::Color, math::Vec2};
+use rand::Rng;
+use vek::Vec2;
+
+use crate::constants::{ANNOTATION_DISTANCE, BASE_BORDER_SIZE, FONT_SIZE};
 
 use edge::Edge;
 
@@ -13,34 +18,39 @@ use self::{
     node::Node,
 };
 
-use crate::tetra_handling::tetra_object::{TetraObject, TetraObjectInfo};
-
+mod barnes_hut;
+pub mod color;
 pub mod edge;
+pub mod generators;
 pub mod gravity;
+pub mod layered;
 pub mod node;
-pub mod random;
 
-pub type Graph = petgraph::Graph<Node, Edge, Directed, u32>;
+pub use color::Color;
+
+// Stable indices, so removing a node does not change what other indices point at.
+pub type Graph = StableGraph<Node, Edge, Directed, u32>;
 pub type Position = Vec2<f32>;
 
 pub trait GraphOnCanvas {
     fn node_from_point(&self, point: Position) -> Option<NodeIndex<u32>>;
-    fn edge_from_point(&self, point: Position) -> Option<petgraph::graph::EdgeIndex>;
-    fn connect_nodes(&mut self, ctx: &mut Context, from: NodeIndex, to: NodeIndex);
+    fn edge_from_point(&self, point: Position) -> Option<EdgeIndex>;
+    fn edge_text_from_point(&self, point: Position) -> Option<EdgeIndex>;
+    fn edge_line(&self, idx: EdgeIndex) -> Option<(Position, Position)>;
+    fn connect_nodes(&mut self, from: NodeIndex, to: NodeIndex);
 
-    fn move_node(&mut self, ctx: &mut Context, idx: NodeIndex, position: Position);
+    fn move_node(&mut self, idx: NodeIndex, position: Position);
 
-    fn update_edges_position(
+    fn push_force(&mut self, push_conf: &PushForceConfig, rng: &mut impl Rng);
+    fn pull_force(&mut self, pull_conf: &PullForceConfig);
+    fn step_layout(
         &mut self,
-        ctx: &mut Context,
-        idx: NodeIndex,
-        position: Position,
-        direction: petgraph::EdgeDirection,
+        push_conf: &PushForceConfig,
+        pull_conf: &PullForceConfig,
+        delta: f32,
+        rng: &mut impl Rng,
     );
 
-    fn push_force(&mut self, push_conf: &PushForceConfig);
-    fn pull_force(&mut self, pull_conf: &PullForceConfig);
-
     fn reset_state(&mut self);
 }
 
@@ -49,91 +59,80 @@ impl GraphOnCanvas for Graph {
         // Reversing to select node that is on top.
         self.node_indices().rev().find(|idx| {
             self.node_weight(*idx)
-                .map_or(false, |node| node.contains(point))
+                .is_some_and(|node| node.contains(point))
         })
     }
 
-    fn edge_from_point(&self, point: Position) -> Option<petgraph::graph::EdgeIndex> {
+    fn edge_from_point(&self, point: Position) -> Option<EdgeIndex> {
         // Reversing to select node that is on top.
         self.edge_indices().rev().find(|idx| {
-            self.edge_weight(*idx)
-                .map_or(false, |edge| edge.is_point_in_shape(point))
+            !self[*idx].is_hidden()
+                && self
+                    .edge_line(*idx)
+                    .is_some_and(|(from, to)| Edge::is_point_in_shape(from, to, point))
         })
     }
 
-    // Wrapper for add_edge function.
-    fn connect_nodes(&mut self, ctx: &mut Context, from: NodeIndex, to: NodeIndex) {
-        let edge = Edge::new(
-            ctx,
-            self.node_weight(from)
-                .map_or(Position::zero(), |node| node.position()),
-            self.node_weight(to)
-                .map_or(Position::zero(), |node| node.position()),
-        );
+    fn edge_text_from_point(&self, point: Position) -> Option<EdgeIndex> {
+        self.edge_indices().rev().find(|idx| {
+            !self[*idx].is_hidden()
+                && self
+                    .edge_line(*idx)
+                    .is_some_and(|(from, to)| Edge::text_contains(from, to, point))
+        })
+    }
 
-        self.update_edge(from, to, edge);
-        println!("Connecting {} -> {}", from.index(), to.index());
+    // Positions of the nodes at both ends.
+    fn edge_line(&self, idx: EdgeIndex) -> Option<(Position, Position)> {
+        let (from, to) = self.edge_endpoints(idx)?;
+        Some((self[from].position(), self[to].position()))
     }
 
-    fn move_node(&mut self, ctx: &mut Context, idx: NodeIndex, to: Position) {
-        if let Some(node) = self.node_weight_mut(idx) {
-            node.set_position(to);
+    // Wrapper for add_edge function.
+    fn connect_nodes(&mut self, from: NodeIndex, to: NodeIndex) {
+        if !self.contains_node(from) || !self.contains_node(to) {
+            return;
         }
 
-        self.update_edges_position(ctx, idx, to, Outgoing);
-        self.update_edges_position(ctx, idx, to, Incoming);
+        self.update_edge(from, to, Edge::new());
     }
 
-    fn update_edges_position(
-        &mut self,
-        ctx: &mut Context,
-        idx: NodeIndex,
-        position: Position,
-        direction: petgraph::EdgeDirection,
-    ) {
-        let mut neigh_iter = self.neighbors_directed(idx, direction).detach();
-
-        while let Some((edge_idx, neigh_idx)) = neigh_iter.next(self) {
-            let neigh_pos_opt = self
-                .node_weight(neigh_idx)
-                .map(|neigh_data| neigh_data.position());
-            let edge_weight_opt = self.edge_weight_mut(edge_idx);
-            if let (Some(neigh_pos), Some(edge_data)) = (neigh_pos_opt, edge_weight_opt) {
-                match direction {
-                    Outgoing => edge_data.update_position(ctx, position, neigh_pos),
-                    Incoming => edge_data.update_position(ctx, neigh_pos, position),
-                };
-            }
+    fn move_node(&mut self, idx: NodeIndex, to: Position) {
+        if let Some(node) = self.node_weight_mut(idx) {
+            node.set_position(to);
         }
     }
 
-    fn push_force(&mut self, push_conf: &PushForceConfig) {
-        for idx in self.node_indices() {
-            for other_idx in self.node_indices() {
-                if idx == other_idx {
-                    continue;
-                }
-
-                self.node_weight(other_idx)
-                    .map(|other_node| other_node.position())
-                    .map(|other_pos| {
-                        self.node_weight_mut(idx)
-                            .map(|node| node.push_away_from_point(other_pos, push_conf))
-                    });
-            }
+    fn push_force(&mut self, push_conf: &PushForceConfig, rng: &mut impl Rng) {
+        let indices = self
+            .node_indices()
+            .filter(|idx| !self[*idx].is_hidden())
+            .collect::<Vec<_>>();
+        let positions = indices
+            .iter()
+            .map(|idx| self[*idx].position())
+            .collect::<Vec<_>>();
+        let forces = barnes_hut::push_forces(&positions, push_conf, rng);
+        for (idx, force) in indices.into_iter().zip(forces) {
+            self[idx].add_force(force);
         }
     }
 
     fn pull_force(&mut self, pull_conf: &PullForceConfig) {
-        for idx in self.node_indices() {
+        let indices = self.node_indices().collect::<Vec<_>>();
+        for idx in indices {
             let mut result = Position::zero();
 
             for edge_in in self.edges_directed(idx, Incoming) {
-                result -= edge_in.weight().calculate_pull_force(pull_conf);
+                if let Some((from, to)) = self.edge_line(edge_in.id()) {
+                    result -= edge_in.weight().calculate_pull_force(from, to, pull_conf);
+                }
             }
 
             for edge_out in self.edges_directed(idx, Outgoing) {
-                result += edge_out.weight().calculate_pull_force(pull_conf);
+                if let Some((from, to)) = self.edge_line(edge_out.id()) {
+                    result += edge_out.weight().calculate_pull_force(from, to, pull_conf);
+                }
             }
 
             if let Some(node) = self.node_weight_mut(idx) {
@@ -142,10 +141,28 @@ impl GraphOnCanvas for Graph {
         }
     }
 
+    // Moves every node by the forces acting on it over `delta` seconds.
+    fn step_layout(
+        &mut self,
+        push_conf: &PushForceConfig,
+        pull_conf: &PullForceConfig,
+        delta: f32,
+        rng: &mut impl Rng,
+    ) {
+        self.push_force(push_conf, rng);
+        self.pull_force(pull_conf);
+
+        for node in self.node_weights_mut() {
+            node.consume_force(delta);
+        }
+    }
+
     fn reset_state(&mut self) {
         for node in self.node_weights_mut() {
             node.set_ignore_force(false);
             node.set_color(Color::WHITE);
+            node.set_annotation(None);
+            node.set_hidden(false);
         }
         for edge in self.edge_weights_mut() {
             edge.reset_state();
@@ -153,29 +170,158 @@ impl GraphOnCanvas for Graph {
     }
 }
 
-impl TetraObject for Graph {
-    fn draw(&mut self, ctx: &mut Context, info: &mut TetraObjectInfo) {
-        for edge in self.edge_weights_mut() {
-            edge.draw(ctx, info);
+// StableGraph has no `into_edge_type`. This rebuilds the graph with another edge type while
+// keeping every node and edge index, holes included, so steps computed on the copy apply to the original.
+pub fn with_edge_type<N: Clone, E: Clone, D: EdgeType>(
+    graph: &StableGraph<N, E, Directed>,
+) -> StableGraph<N, E, D> {
+    let mut result = StableGraph::<N, E, D>::default();
+    let (filler_node, filler_edge) = match (
+        graph.node_weights().next().cloned(),
+        graph.edge_weights().next().cloned(),
+    ) {
+        (Some(node), edge) => (node, edge),
+        (None, _) => return result,
+    };
+
+    let node_bound = graph.node_indices().map(|idx| idx.index() + 1).max();
+    for nr in 0..node_bound.unwrap_or(0) {
+        let weight = graph.node_weight(NodeIndex::new(nr)).cloned();
+        result.add_node(weight.unwrap_or_else(|| filler_node.clone()));
+    }
+
+    if let Some(filler_edge) = filler_edge {
+        for nr in 0..graph.edge_bound() {
+            let idx = EdgeIndex::new(nr);
+            match (graph.edge_endpoints(idx), graph.edge_weight(idx)) {
+                (Some((from, to)), Some(weight)) => result.add_edge(from, to, weight.clone()),
+                _ => result.add_edge(NodeIndex::new(0), NodeIndex::new(0), filler_edge.clone()),
+            };
         }
+    }
 
-        for node in self.node_weights_mut() {
-            node.draw(ctx, info);
+    for nr in (0..graph.edge_bound()).rev() {
+        if graph.edge_weight(EdgeIndex::new(nr)).is_none() {
+            result.remove_edge(EdgeIndex::new(nr));
+        }
+    }
+    for nr in (0..node_bound.unwrap_or(0)).rev() {
+        if !graph.contains_node(NodeIndex::new(nr)) {
+            result.remove_node(NodeIndex::new(nr));
         }
     }
 
-    fn update(&mut self, ctx: &mut Context, info: &mut TetraObjectInfo) {
-        self.push_force(info.ui_data().push_conf());
-        self.pull_force(info.ui_data().pull_conf());
+    result
+}
 
-        for node_idx in self.node_indices() {
-            if let Some(pos) = self.node_weight_mut(node_idx).map(|node| {
-                node.update(ctx, info);
-                node.consume_force(ctx);
-                node.position()
-            }) {
-                self.move_node(ctx, node_idx, pos)
+// Smallest and biggest corner of everything drawn, annotations included, or None for an empty graph.
+pub fn drawn_bounds(graph: &Graph) -> Option<(Position, Position)> {
+    graph
+        .node_weights()
+        .filter(|node| !node.is_hidden())
+        .flat_map(|node| {
+            let reach = node.radius() + BASE_BORDER_SIZE;
+            let mut corners = vec![node.position() - reach, node.position() + reach];
+            if node.annotation().is_some() {
+                corners.push(node.position() - Position::new(0., ANNOTATION_DISTANCE + FONT_SIZE));
             }
+            corners
+        })
+        .fold(None, |bounds, point| match bounds {
+            Some((min, max)) => Some((
+                Position::partial_min(min, point),
+                Position::partial_max(max, point),
+            )),
+            None => Some((point, point)),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::{EdgeIndex, NodeIndex};
+    use petgraph::stable_graph::StableGraph;
+    use petgraph::Undirected;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::gravity::{PullForceConfig, PushForceConfig};
+    use super::node::Node;
+    use super::{with_edge_type, Graph, GraphOnCanvas, Position};
+
+    #[test]
+    fn layout_pulls_connected_nodes_together() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(Node::new(Position::new(0., 0.)));
+        let b = graph.add_node(Node::new(Position::new(1000., 0.)));
+        let lonely = graph.add_node(Node::new(Position::new(0., 5000.)));
+        graph.connect_nodes(a, b);
+        let (push_conf, pull_conf) = (
+            PushForceConfig::new(1000., 150.),
+            PullForceConfig::new(100., 500.),
+        );
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            graph.step_layout(&push_conf, &pull_conf, 0.01, &mut rng);
+        }
+
+        let distance = graph[a].position().distance(graph[b].position());
+        assert!(distance < 500., "{}", distance);
+        assert_eq!(graph[lonely].position(), Position::new(0., 5000.));
+    }
+
+    #[test]
+    fn layout_is_reproducible_with_a_seed() {
+        let mut graph = Graph::new();
+        for _ in 0..5 {
+            graph.add_node(Node::new(Position::zero()));
         }
+        let (push_conf, pull_conf) = (
+            PushForceConfig::new(1000., 150.),
+            PullForceConfig::new(100., 500.),
+        );
+        let spread = |seed| {
+            let mut graph = graph.clone();
+            let mut rng = StdRng::seed_from_u64(seed);
+            for _ in 0..10 {
+                graph.step_layout(&push_conf, &pull_conf, 0.01, &mut rng);
+            }
+            graph
+                .node_weights()
+                .map(|node| node.position())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(spread(7), spread(7));
+        assert_ne!(spread(7), spread(8));
+    }
+
+    #[test]
+    fn edge_type_change_keeps_indices() {
+        let mut graph = StableGraph::<u32, u32>::new();
+        let a = graph.add_node(0);
+        let b = graph.add_node(1);
+        let c = graph.add_node(2);
+        let d = graph.add_node(3);
+        let ab = graph.add_edge(a, b, 10);
+        let bc = graph.add_edge(b, c, 20);
+        let cd = graph.add_edge(c, d, 30);
+        graph.remove_node(b);
+        graph.remove_edge(ab);
+
+        let undirected = with_edge_type::<_, _, Undirected>(&graph);
+
+        assert_eq!(undirected.node_count(), 3);
+        assert!(!undirected.contains_node(b));
+        assert_eq!(undirected[a], 0);
+        assert_eq!(undirected[c], 2);
+        assert_eq!(undirected[d], 3);
+        assert_eq!(undirected.edge_count(), 1);
+        assert_eq!(undirected.edge_weight(bc), None);
+        assert_eq!(undirected.edge_endpoints(cd), Some((c, d)));
+        assert_eq!(undirected[cd], 30);
+        assert!(undirected.find_edge(d, c).is_some());
+        assert_eq!(undirected.edge_weight(EdgeIndex::new(0)), None);
+        assert_eq!(undirected.node_weight(NodeIndex::new(1)), None);
     }
 }
@@ -1,19 +1,8 @@
 use std::f32;
 
-use crate::game_state::AppMode;
-use tetra::graphics::mesh::ShapeStyle;
-use tetra::graphics::text::{Font, Text};
-use tetra::graphics::DrawParams;
-use tetra::graphics::{mesh::Mesh, Color};
-use tetra::input::Key;
-use tetra::math::Vec2;
-use tetra::{input, Context};
+use super::{Color, Position};
 
-use super::gravity::PushForceConfig;
-use super::Position;
-
-use crate::constants::{BASE_BORDER_SIZE, BASE_RADIUS, FONT_SIZE, HIGHLIGHT_SCALE};
-use crate::tetra_handling::tetra_object::{TetraObject, TetraObjectInfo};
+use crate::constants::BASE_RADIUS;
 
 #[derive(Clone)]
 pub enum NodeHighlight {
@@ -21,6 +10,15 @@ pub enum NodeHighlight {
     Normal,
 }
 
+// Everything algorithm steps may change, so a step can be taken back.
+#[derive(Clone, PartialEq, Debug)]
+pub struct NodeVisuals {
+    color: Color,
+    border_color: Color,
+    annotation: Option<String>,
+    hidden: bool,
+}
+
 #[derive(Clone)]
 pub struct Node {
     position: Position,
@@ -34,19 +32,15 @@ pub struct Node {
     current_force: Position,
     ignore_force: bool,
 
-    // To change colors this has to be separate
-    circle: Mesh,
-    border: Mesh,
-
     node_text: String,
-    font: Font,
+    // Drawn above the node, algorithms use it to show values like distances.
+    annotation: Option<String>,
+    // Not drawn, clicked or moved, e.g. while a generated graph is still growing.
+    hidden: bool,
 }
 
 impl Node {
-    // Adding font here is not perfect, but I don't see better solution. Creating font with every frame is expensive, since it has to cache the file again and again.
-    // creating the font as a static is out of the question, since its ctro takes ctx as argument. Hence I believe it should be stored in gamestate and cloned every time
-    // new nodes is created (cloning node is cheap, its just Rc under the hood).
-    pub fn new(ctx: &mut Context, position: Position, font: Font) -> Node {
+    pub fn new(position: Position) -> Node {
         Node {
             position,
             radius: BASE_RADIUS,
@@ -54,36 +48,20 @@ impl Node {
             color: Color::WHITE,
             current_force: Position::zero(),
             ignore_force: false,
-            border: Mesh::circle(
-                ctx,
-                ShapeStyle::Stroke(BASE_BORDER_SIZE),
-                Vec2 { x: 0.0, y: 0.0 },
-                BASE_RADIUS,
-            )
-            .unwrap(),
-            circle: Mesh::circle(ctx, ShapeStyle::Fill, Vec2 { x: 0.0, y: 0.0 }, BASE_RADIUS)
-                .unwrap(),
             highlight: NodeHighlight::Normal,
             node_text: String::from(""),
-            font,
+            annotation: None,
+            hidden: false,
         }
     }
 
     // Is point in this shape?
     pub fn contains(&self, point: Position) -> bool {
-        Vec2::distance(point, self.position) <= self.radius
+        !self.hidden && Position::distance(point, self.position) <= self.radius
     }
 
-    fn draw_params(&self, position: Position) -> DrawParams {
-        DrawParams::new()
-            .scale(
-                if matches!(self.highlight, NodeHighlight::Highlighted) || self.contains(position) {
-                    HIGHLIGHT_SCALE
-                } else {
-                    Vec2::one()
-                },
-            )
-            .position(self.position)
+    pub fn radius(&self) -> f32 {
+        self.radius
     }
 
     pub fn color(&self) -> Color {
@@ -94,6 +72,42 @@ impl Node {
         self.color = color;
     }
 
+    pub fn border_color(&self) -> Color {
+        self.border_color
+    }
+
+    pub fn set_border_color(&mut self, color: Color) {
+        self.border_color = color;
+    }
+
+    pub fn visuals(&self) -> NodeVisuals {
+        NodeVisuals {
+            color: self.color,
+            border_color: self.border_color,
+            annotation: self.annotation.clone(),
+            hidden: self.hidden,
+        }
+    }
+
+    pub fn set_visuals(&mut self, visuals: NodeVisuals) {
+        self.color = visuals.color;
+        self.border_color = visuals.border_color;
+        self.annotation = visuals.annotation;
+        self.hidden = visuals.hidden;
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub fn is_highlighted(&self) -> bool {
+        matches!(self.highlight, NodeHighlight::Highlighted)
+    }
+
     pub fn set_highlight(&mut self, highlight: NodeHighlight) {
         self.highlight = highlight;
     }
@@ -106,31 +120,33 @@ impl Node {
         self.position = position;
     }
 
-    pub fn add_force(&mut self, force: Position) {
-        self.current_force += force;
+    pub fn node_text(&self) -> &str {
+        &self.node_text
     }
 
-    pub fn push_away_from_point(&mut self, point: Position, push_conf: &PushForceConfig) {
-        let mut direction_to = self.position() - point;
-        if direction_to.is_approx_zero() {
-            direction_to = Position::up().rotated_z(rand::random::<f32>());
-        }
-        let push_direction = direction_to.normalized();
-        let force_div = 1. - self.position().distance(point) / push_conf.distance();
+    pub fn set_node_text(&mut self, text: &str) {
+        self.node_text = String::from(text);
+    }
 
-        if force_div <= 0. {
-            return;
-        }
+    pub fn annotation(&self) -> Option<&str> {
+        self.annotation.as_deref()
+    }
 
-        self.current_force += push_direction * push_conf.force() * force_div;
+    pub fn set_annotation(&mut self, annotation: Option<String>) {
+        self.annotation = annotation;
     }
 
-    pub fn consume_force(&mut self, ctx: &mut Context) {
+    pub fn add_force(&mut self, force: Position) {
+        self.current_force += force;
+    }
+
+    // Moves the node by the gathered force over `delta` seconds.
+    pub fn consume_force(&mut self, delta: f32) {
         if self.ignore_force {
             return;
         }
 
-        self.position += self.current_force * tetra::time::get_delta_time(ctx).as_secs_f32();
+        self.position += self.current_force * delta;
         self.current_force = Position::zero();
     }
 
@@ -138,64 +154,4 @@ impl Node {
         self.ignore_force = value;
         self.current_force = Position::zero();
     }
-
-    pub fn draw_text(&mut self, ctx: &mut Context, rotation: f32, mouse_position: Vec2<f32>) {
-        if self.node_text.is_empty() {
-            return;
-        }
-
-        let mut text = Text::new(&self.node_text, self.font.clone());
-
-        // This turns on text wrapping after BASE_RADIUS
-        text.set_max_width(Some(BASE_RADIUS));
-
-        let mut text_params = self.draw_params(mouse_position).color(Color::BLACK);
-
-        // We set the origin to the center of the text, so rotation will behave nicely.
-        text_params.origin = text.get_bounds(ctx).unwrap().bottom_right() / 2.;
-        text_params.position = self.position;
-        // We do not want the text to rotate.
-        text_params.rotation = -rotation;
-        text_params.scale /= FONT_SIZE;
-        text.draw(ctx, text_params);
-    }
-
-    pub fn input(&mut self, ctx: &mut Context, mode: &mut AppMode) {
-        if let Some(new_input) = input::get_text_input(ctx) {
-            if self.node_text.len() <= 10 {
-                self.node_text.push_str(new_input);
-            }
-        }
-
-        if input::is_key_pressed(ctx, Key::Backspace) {
-            self.node_text.pop();
-        }
-
-        if input::is_key_pressed(ctx, Key::Enter) {
-            *mode = AppMode::Normal;
-        }
-    }
-}
-
-impl TetraObject for Node {
-    fn draw(&mut self, ctx: &mut Context, info: &mut TetraObjectInfo) {
-        let params = self.draw_params(info.camera().mouse_position(ctx));
-        self.circle.draw(ctx, params.clone().color(self.color()));
-
-        self.border.draw(ctx, params.color(self.border_color));
-
-        self.draw_text(
-            ctx,
-            info.camera().rotation,
-            info.camera().mouse_position(ctx),
-        );
-    }
-
-    fn update(&mut self, ctx: &mut Context, info: &mut TetraObjectInfo) {
-        if let AppMode::Write = info.mode() {
-            if self.contains(info.camera().mouse_position(ctx)) {
-                self.input(ctx, info.mode_mut());
-            }
-        }
-    }
 }
@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeDirection::{Incoming, Outgoing};
+
+use crate::graph::edge::Edge;
+use crate::graph::node::Node;
+use crate::graph::{Graph, GraphOnCanvas, Position};
+
+// Commands describe an edit that has already been done to the graph.
+// History is strictly LIFO, so a command is always undone on the graph it left behind.
+// Removed edges come back at their index, so parallel edges stay apart. Other edits refer to edges
+// by their endpoints.
+#[derive(Clone)]
+pub enum Command {
+    AddNode {
+        idx: NodeIndex,
+        node: Node,
+    },
+    RemoveNode {
+        idx: NodeIndex,
+        node: Node,
+        edges: Vec<(EdgeIndex, NodeIndex, NodeIndex, Edge)>,
+    },
+    RemoveEdge {
+        idx: EdgeIndex,
+        from: NodeIndex,
+        to: NodeIndex,
+        edge: Edge,
+    },
+    Connect {
+        from: NodeIndex,
+        to: NodeIndex,
+        replaced: Option<Edge>,
+    },
+    Move {
+        idx: NodeIndex,
+        from: Position,
+        to: Position,
+    },
+    EditText {
+        idx: NodeIndex,
+        before: String,
+        after: String,
+    },
+    EditEdgeText {
+        from: NodeIndex,
+        to: NodeIndex,
+        before: String,
+        after: String,
+    },
+    Clique {
+        added: Vec<(NodeIndex, NodeIndex)>,
+    },
+    ReplaceGraph {
+        before: Graph,
+        after: Graph,
+    },
+    // Several nodes moved at once, each with where it started and where it ended up.
+    Arrange {
+        moves: Vec<(NodeIndex, Position, Position)>,
+    },
+}
+
+// Puts a node back at the index it was removed from. Vacant slots are handed out by the graph,
+// so nodes are added until the wanted slot comes up, and the extra ones are removed again.
+fn add_node_at<N: Clone, E>(graph: &mut StableGraph<N, E>, idx: NodeIndex, weight: N) {
+    // Can't happen when the history is replayed in order, but it would never end otherwise.
+    if graph.contains_node(idx) {
+        return;
+    }
+
+    let mut extra = Vec::new();
+    loop {
+        let added = graph.add_node(weight.clone());
+        if added == idx {
+            break;
+        }
+        extra.push(added);
+    }
+    // Removing in reverse keeps the order of vacant slots as it was.
+    for other in extra.into_iter().rev() {
+        graph.remove_node(other);
+    }
+}
+
+// Like `add_node_at` for edges, the extra edges connect the same nodes.
+fn add_edge_at<N, E: Clone>(
+    graph: &mut StableGraph<N, E>,
+    idx: EdgeIndex,
+    from: NodeIndex,
+    to: NodeIndex,
+    weight: E,
+) {
+    if graph.edge_weight(idx).is_some() {
+        return;
+    }
+
+    let mut extra = Vec::new();
+    loop {
+        let added = graph.add_edge(from, to, weight.clone());
+        if added == idx {
+            break;
+        }
+        extra.push(added);
+    }
+    for other in extra.into_iter().rev() {
+        graph.remove_edge(other);
+    }
+}
+
+// Adds a node in a slot that none of `reserved` points at. The graph hands out vacant slots first,
+// and steps of an algorithm that is still shown would color the new node for the removed one.
+fn add_node_avoiding<N: Clone, E>(
+    graph: &mut StableGraph<N, E>,
+    weight: N,
+    reserved: &HashSet<NodeIndex>,
+) -> NodeIndex {
+    let mut extra = Vec::new();
+    let idx = loop {
+        let added = graph.add_node(weight.clone());
+        if !reserved.contains(&added) {
+            break added;
+        }
+        extra.push(added);
+    };
+    for other in extra.into_iter().rev() {
+        graph.remove_node(other);
+    }
+    idx
+}
+
+fn remove_edge_between(graph: &mut Graph, from: NodeIndex, to: NodeIndex) -> Option<Edge> {
+    graph
+        .find_edge(from, to)
+        .and_then(|idx| graph.remove_edge(idx))
+}
+
+fn set_edge_text(graph: &mut Graph, from: NodeIndex, to: NodeIndex, text: &str) {
+    if let Some(edge) = graph
+        .find_edge(from, to)
+        .and_then(|idx| graph.edge_weight_mut(idx))
+    {
+        edge.set_text(text);
+    }
+}
+
+// Arranged nodes ignore the layout forces while they move, they are free again once the
+// arrangement is undone or redone.
+fn release_node(graph: &mut Graph, idx: NodeIndex, position: Position) {
+    if let Some(node) = graph.node_weight_mut(idx) {
+        node.set_position(position);
+        node.set_ignore_force(false);
+    }
+}
+
+impl Command {
+    // `reserved` are indices something else still refers to, the node won't get one of them.
+    pub fn add_node(graph: &mut Graph, node: Node, reserved: &HashSet<NodeIndex>) -> Command {
+        let idx = add_node_avoiding(graph, node.clone(), reserved);
+        Command::AddNode { idx, node }
+    }
+
+    pub fn remove_node(graph: &mut Graph, idx: NodeIndex) -> Option<Command> {
+        let mut edges: Vec<(EdgeIndex, NodeIndex, NodeIndex, Edge)> = graph
+            .edges_directed(idx, Outgoing)
+            .chain(graph.edges_directed(idx, Incoming))
+            .map(|edge| {
+                (
+                    edge.id(),
+                    edge.source(),
+                    edge.target(),
+                    edge.weight().clone(),
+                )
+            })
+            .collect();
+        // A self loop is listed both ways.
+        edges.sort_by_key(|(edge_idx, ..)| *edge_idx);
+        edges.dedup_by_key(|(edge_idx, ..)| *edge_idx);
+        graph
+            .remove_node(idx)
+            .map(|node| Command::RemoveNode { idx, node, edges })
+    }
+
+    pub fn remove_edge(graph: &mut Graph, idx: EdgeIndex) -> Option<Command> {
+        let (from, to) = graph.edge_endpoints(idx)?;
+        graph.remove_edge(idx).map(|edge| Command::RemoveEdge {
+            idx,
+            from,
+            to,
+            edge,
+        })
+    }
+
+    pub fn connect(graph: &mut Graph, from: NodeIndex, to: NodeIndex) -> Command {
+        let replaced = graph
+            .find_edge(from, to)
+            .and_then(|idx| graph.edge_weight(idx))
+            .cloned();
+        graph.connect_nodes(from, to);
+        Command::Connect { from, to, replaced }
+    }
+
+    pub fn clique(graph: &mut Graph) -> Command {
+        let mut added = Vec::new();
+        let indices = graph.node_indices().collect::<Vec<_>>();
+        for from in indices.iter().copied() {
+            for to in indices.iter().copied() {
+                if from != to && graph.find_edge(from, to).is_none() {
+                    graph.connect_nodes(from, to);
+                    added.push((from, to));
+                }
+            }
+        }
+        Command::Clique { added }
+    }
+
+    pub fn undo(&self, graph: &mut Graph) {
+        match self {
+            Command::AddNode { idx, .. } => {
+                graph.remove_node(*idx);
+            }
+            Command::RemoveNode { idx, node, edges } => {
+                add_node_at(graph, *idx, node.clone());
+                for (edge_idx, from, to, edge) in edges {
+                    add_edge_at(graph, *edge_idx, *from, *to, edge.clone());
+                }
+            }
+            Command::RemoveEdge {
+                idx,
+                from,
+                to,
+                edge,
+            } => add_edge_at(graph, *idx, *from, *to, edge.clone()),
+            Command::Connect { from, to, replaced } => match replaced {
+                Some(edge) => {
+                    graph.update_edge(*from, *to, edge.clone());
+                }
+                None => {
+                    remove_edge_between(graph, *from, *to);
+                }
+            },
+            Command::Move { idx, from, .. } => graph.move_node(*idx, *from),
+            Command::EditText { idx, before, .. } => {
+                if let Some(node) = graph.node_weight_mut(*idx) {
+                    node.set_node_text(before);
+                }
+            }
+            Command::EditEdgeText {
+                from, to, before, ..
+            } => set_edge_text(graph, *from, *to, before),
+            Command::Clique { added } => {
+                for (from, to) in added {
+                    remove_edge_between(graph, *from, *to);
+                }
+            }
+            Command::ReplaceGraph { before, .. } => *graph = before.clone(),
+            Command::Arrange { moves } => {
+                for (idx, from, _) in moves {
+                    release_node(graph, *idx, *from);
+                }
+            }
+        }
+    }
+
+    pub fn redo(&self, graph: &mut Graph) {
+        match self {
+            Command::AddNode { idx, node } => {
+                add_node_at(graph, *idx, node.clone());
+            }
+            Command::RemoveNode { idx, .. } => {
+                graph.remove_node(*idx);
+            }
+            Command::RemoveEdge { idx, .. } => {
+                graph.remove_edge(*idx);
+            }
+            Command::Connect { from, to, .. } => graph.connect_nodes(*from, *to),
+            Command::Move { idx, to, .. } => graph.move_node(*idx, *to),
+            Command::EditText { idx, after, .. } => {
+                if let Some(node) = graph.node_weight_mut(*idx) {
+                    node.set_node_text(after);
+                }
+            }
+            Command::EditEdgeText {
+                from, to, after, ..
+            } => set_edge_text(graph, *from, *to, after),
+            Command::Clique { added } => {
+                for (from, to) in added {
+                    graph.connect_nodes(*from, *to);
+                }
+            }
+            Command::ReplaceGraph { after, .. } => *graph = after.clone(),
+            Command::Arrange { moves } => {
+                for (idx, _, to) in moves {
+                    release_node(graph, *idx, *to);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+    use petgraph::stable_graph::StableGraph;
+
+    use std::collections::HashSet;
+
+    use super::{add_node_at, add_node_avoiding, Command};
+    use crate::graph::edge::Edge;
+    use crate::graph::node::Node;
+    use crate::graph::{Graph, Position};
+
+    #[test]
+    fn node_returns_to_its_slot() {
+        let mut graph = StableGraph::<u32, u32>::new();
+        let indices = (0..5).map(|nr| graph.add_node(nr)).collect::<Vec<_>>();
+        graph.remove_node(indices[1]);
+        graph.remove_node(indices[3]);
+
+        // The graph would hand out slot 3 first.
+        add_node_at(&mut graph, indices[1], 1);
+
+        assert_eq!(graph[indices[1]], 1);
+        assert!(!graph.contains_node(indices[3]));
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.add_node(10), indices[3]);
+        assert_eq!(graph.add_node(11), NodeIndex::new(5));
+    }
+
+    #[test]
+    fn new_node_skips_reserved_slots() {
+        let mut graph = StableGraph::<u32, u32>::new();
+        let indices = (0..4).map(|nr| graph.add_node(nr)).collect::<Vec<_>>();
+        graph.remove_node(indices[1]);
+        graph.remove_node(indices[2]);
+
+        let reserved = HashSet::from([indices[2]]);
+        assert_eq!(add_node_avoiding(&mut graph, 5, &reserved), indices[1]);
+        assert!(!graph.contains_node(indices[2]));
+        assert_eq!(
+            add_node_avoiding(&mut graph, 6, &reserved),
+            NodeIndex::new(4)
+        );
+        // Once nothing refers to it, the slot is used again.
+        assert_eq!(graph.add_node(7), indices[2]);
+    }
+
+    #[test]
+    fn arrangement_round_trip_frees_nodes() {
+        let mut graph = Graph::new();
+        let (from, to) = (Position::zero(), Position::new(100., 50.));
+        let idx = graph.add_node(Node::new(to));
+        // Left behind by the animation.
+        graph[idx].set_ignore_force(true);
+        let command = Command::Arrange {
+            moves: vec![(idx, from, to)],
+        };
+
+        command.undo(&mut graph);
+        assert_eq!(graph[idx].position(), from);
+        graph[idx].add_force(Position::new(1., 0.));
+        graph[idx].consume_force(1.);
+        assert_eq!(graph[idx].position(), Position::new(1., 0.));
+
+        graph[idx].set_ignore_force(true);
+        command.redo(&mut graph);
+        assert_eq!(graph[idx].position(), to);
+        graph[idx].add_force(Position::new(1., 0.));
+        graph[idx].consume_force(1.);
+        assert_eq!(graph[idx].position(), Position::new(101., 50.));
+    }
+
+    #[test]
+    fn removal_round_trip_keeps_parallel_edges_and_loops() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(Node::new(Position::zero()));
+        let b = graph.add_node(Node::new(Position::new(100., 0.)));
+        let weighted = |weight: f32| {
+            let mut edge = Edge::new();
+            edge.set_weight(weight);
+            edge
+        };
+        let edges = [(a, b, 1.), (a, b, 2.), (a, a, 3.), (b, a, 4.), (b, b, 5.)]
+            .into_iter()
+            .map(|(from, to, weight)| graph.add_edge(from, to, weighted(weight)))
+            .collect::<Vec<_>>();
+        let edge_set = |graph: &Graph| {
+            graph
+                .edge_indices()
+                .map(|idx| {
+                    let (from, to) = graph.edge_endpoints(idx).unwrap();
+                    (idx, from, to, graph[idx].weight())
+                })
+                .collect::<Vec<_>>()
+        };
+        let all = edge_set(&graph);
+
+        let command = Command::remove_node(&mut graph, a).unwrap();
+        assert_eq!(edge_set(&graph), vec![all[4]]);
+        command.undo(&mut graph);
+        assert_eq!(edge_set(&graph), all);
+        command.redo(&mut graph);
+        command.undo(&mut graph);
+        assert_eq!(edge_set(&graph), all);
+
+        // Only the removed one of two parallel edges goes away again.
+        let command = Command::remove_edge(&mut graph, edges[1]).unwrap();
+        command.undo(&mut graph);
+        assert_eq!(edge_set(&graph), all);
+        command.redo(&mut graph);
+        assert_eq!(
+            graph.edge_weight(edges[0]).map(|edge| edge.weight()),
+            Some(1.)
+        );
+        assert!(graph.edge_weight(edges[1]).is_none());
+    }
+}
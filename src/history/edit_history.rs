@@ -0,0 +1,48 @@
+use crate::graph::Graph;
+
+use super::Command;
+
+#[derive(Default)]
+pub struct EditHistory {
+    undo_stack: Vec<Command>,
+    redo_stack: Vec<Command>,
+}
+
+impl EditHistory {
+    pub fn new() -> EditHistory {
+        EditHistory::default()
+    }
+
+    // Records an edit that was already applied. A new edit makes the redo stack meaningless.
+    pub fn push(&mut self, command: Command) {
+        self.undo_stack.push(command);
+        self.redo_stack.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn undo(&mut self, graph: &mut Graph) {
+        if let Some(command) = self.undo_stack.pop() {
+            command.undo(graph);
+            self.redo_stack.push(command);
+        }
+    }
+
+    pub fn redo(&mut self, graph: &mut Graph) {
+        if let Some(command) = self.redo_stack.pop() {
+            command.redo(graph);
+            self.undo_stack.push(command);
+        }
+    }
+}
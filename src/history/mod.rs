@@ -0,0 +1,5 @@
+mod command;
+mod edit_history;
+
+pub use command::Command;
+pub use edit_history::EditHistory;
@@ -1,15 +1,19 @@
+use std::collections::HashSet;
+
 use petgraph::graph::NodeIndex;
-use tetra::graphics::text::Font;
-use tetra::math::Vec2;
-use tetra::Context;
 
-use crate::graph::node::NodeHighlight;
-use crate::graph::GraphOnCanvas;
-use crate::graph::{node::Node, Graph};
+use projekt::graph::node::NodeHighlight;
+use projekt::graph::GraphOnCanvas;
+use projekt::graph::{node::Node, Graph, Position};
+use projekt::history::Command;
 
 #[derive(Default)]
 pub struct StateData {
     pub selected_node: Option<NodeIndex<u32>>,
+    // Where the moved node was picked up, so the whole drag is a single edit.
+    pub start_position: Option<Position>,
+    // Where shortest paths should lead to, picked in Select mode.
+    pub target_node: Option<NodeIndex<u32>>,
 }
 
 pub enum InputState {
@@ -21,53 +25,60 @@ pub enum InputState {
 }
 
 impl InputState {
+    // Returns the edit that the click made to the graph, if any. A new node doesn't take one of
+    // the `reserved` indices.
     pub fn on_left_click(
         &mut self,
-        ctx: &mut Context,
         graph: &mut Graph,
-        position: Vec2<f32>,
-        font: Font,
-    ) {
+        position: Position,
+        reserved: &HashSet<NodeIndex>,
+    ) -> Option<Command> {
         match self {
-            InputState::Add => {
-                graph.add_node(Node::new(ctx, position, font));
-            }
+            InputState::Add => Some(Command::add_node(graph, Node::new(position), reserved)),
             InputState::Remove => {
-                graph
-                    .node_from_point(position)
-                    .map(|idx| graph.remove_node(idx));
-
-                graph.edge_from_point(position).map(|idx| {
-                    //println!("{}", idx.index());
-                    graph.remove_edge(idx)
-                });
+                if let Some(idx) = graph.node_from_point(position) {
+                    Command::remove_node(graph, idx)
+                } else {
+                    graph
+                        .edge_from_point(position)
+                        .and_then(|idx| Command::remove_edge(graph, idx))
+                }
             }
             InputState::Move(data) => match data.selected_node {
                 Some(node_idx) => {
                     data.selected_node = None;
-                    if let Some(node) = graph.node_weight_mut(node_idx) {
-                        node.set_ignore_force(false)
-                    }
+                    let start_position = data.start_position.take();
+                    graph.node_weight_mut(node_idx).and_then(|node| {
+                        node.set_ignore_force(false);
+                        start_position.map(|from| Command::Move {
+                            idx: node_idx,
+                            from,
+                            to: node.position(),
+                        })
+                    })
                 }
                 None => {
                     data.selected_node = graph.node_from_point(position);
                     if let Some(node_idx) = data.selected_node {
                         if let Some(node) = graph.node_weight_mut(node_idx) {
-                            node.set_ignore_force(true)
+                            node.set_ignore_force(true);
+                            data.start_position = Some(node.position());
                         }
                     }
+                    None
                 }
             },
             InputState::Connect(data) => match data.selected_node {
                 Some(from) => {
-                    if let Some(to) = graph.node_from_point(position) {
-                        graph.connect_nodes(ctx, from, to)
-                    }
+                    let command = graph
+                        .node_from_point(position)
+                        .map(|to| Command::connect(graph, from, to));
                     if let Some(node) = graph.node_weight_mut(from) {
                         node.set_highlight(NodeHighlight::Normal)
                     }
 
                     data.selected_node = None;
+                    command
                 }
                 None => {
                     data.selected_node = graph.node_from_point(position);
@@ -77,29 +88,60 @@ impl InputState {
                     {
                         node.set_highlight(NodeHighlight::Highlighted)
                     }
+                    None
                 }
             },
             InputState::Select(data) => {
+                let clicked = graph.node_from_point(position);
+                // Another node clicked right after the start becomes the target, the next click
+                // picks a new start.
+                if let (Some(start), None, Some(idx)) =
+                    (data.selected_node, data.target_node, clicked)
+                {
+                    if idx != start {
+                        data.target_node = Some(idx);
+                        return None;
+                    }
+                }
                 if let Some(idx) = data.selected_node {
                     if let Some(node) = graph.node_weight_mut(idx) {
                         node.set_highlight(NodeHighlight::Normal)
                     }
                 }
-                data.selected_node = graph.node_from_point(position);
+                data.target_node = None;
+                data.selected_node = clicked;
                 if let Some(idx) = data.selected_node {
                     if let Some(node) = graph.node_weight_mut(idx) {
                         node.set_highlight(NodeHighlight::Highlighted)
                     }
                 }
+                None
+            }
+        }
+    }
+
+    // Forgets the selected node, used when the graph changed under the selection.
+    pub fn clear_selection(&mut self, graph: &mut Graph) {
+        if let InputState::Move(data) | InputState::Connect(data) | InputState::Select(data) = self
+        {
+            if let Some(node) = data
+                .selected_node
+                .take()
+                .and_then(|idx| graph.node_weight_mut(idx))
+            {
+                node.set_highlight(NodeHighlight::Normal);
+                node.set_ignore_force(false);
             }
+            data.start_position = None;
+            data.target_node = None;
         }
     }
 
-    pub fn on_mouse_drag(&mut self, ctx: &mut Context, graph: &mut Graph, position: Vec2<f32>) {
+    pub fn on_mouse_drag(&mut self, graph: &mut Graph, position: Position) {
         if let InputState::Move(data) = self {
             match data.selected_node {
                 None => (),
-                Some(node_idx) => graph.move_node(ctx, node_idx, position),
+                Some(node_idx) => graph.move_node(node_idx, position),
             }
         }
     }
@@ -117,3 +159,67 @@ impl PartialEq for InputState {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use projekt::graph::node::Node;
+    use projekt::graph::{Color, Graph, GraphOnCanvas, Position};
+    use projekt::step_algorithms::{Bfs, StepAlgorithm};
+
+    use super::{InputState, StateData};
+
+    #[test]
+    fn editing_while_an_algorithm_is_shown() {
+        // A path 0 - 1 - 2 - 3, searched from 0.
+        let mut graph = Graph::new();
+        let nodes = (0..4)
+            .map(|nr| graph.add_node(Node::new(Position::new(nr as f32 * 100., 0.))))
+            .collect::<Vec<_>>();
+        for pair in nodes.windows(2) {
+            graph.connect_nodes(pair[0], pair[1]);
+        }
+        let mut bfs = Bfs::from_graph(&graph);
+        bfs.run(&graph, nodes[0]);
+        let mut result = bfs.result();
+        result.show_algorithm(&mut graph);
+        result.step_forward(&mut graph);
+
+        let mut select = InputState::Select(StateData::default());
+        select.on_left_click(&mut graph, Position::new(300., 0.), &HashSet::new());
+        let reserved = result.referenced_nodes();
+        InputState::Remove.on_left_click(&mut graph, Position::new(100., 0.), &reserved);
+        InputState::Add.on_left_click(&mut graph, Position::new(0., 100.), &reserved);
+
+        let added = graph
+            .node_indices()
+            .find(|idx| !nodes.contains(idx))
+            .unwrap();
+        assert_ne!(added, nodes[1]);
+        match &select {
+            InputState::Select(data) => assert_eq!(data.selected_node, Some(nodes[3])),
+            _ => unreachable!(),
+        }
+        assert_eq!(graph[nodes[3]].position(), Position::new(300., 0.));
+
+        // The remaining steps still show the surviving nodes and leave the new one alone.
+        let steps = result.steps().len();
+        result.jump_to(&mut graph, steps);
+        for idx in [nodes[0], nodes[2], nodes[3]] {
+            assert_eq!(graph[idx].color(), Color::GREEN);
+        }
+        assert_eq!(graph[added].color(), Color::WHITE);
+        result.step_back(&mut graph);
+        assert_eq!(graph[nodes[3]].color(), Color::rgb8(200, 200, 200));
+        result.jump_to(&mut graph, 0);
+        assert!(graph
+            .node_weights()
+            .all(|node| node.color() == Color::WHITE));
+
+        // Steps for the removed node are stale, not moved over to the new one.
+        let stale = result.stale_steps(&graph);
+        assert!(stale.iter().any(|stale| stale.nodes() == &vec![nodes[1]]));
+        assert!(stale.iter().all(|stale| !stale.nodes().contains(&added)));
+    }
+}
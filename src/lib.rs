@@ -0,0 +1,9 @@
+// Graph model, layout forces, generators and step algorithms. Everything here is plain data
+// (positions, colors, labels), the app in main.rs draws it with tetra. `offscreen` draws it into
+// images without a window.
+pub mod constants;
+pub mod file_handling;
+pub mod graph;
+pub mod history;
+pub mod offscreen;
+pub mod step_algorithms;
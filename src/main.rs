@@ -1,22 +1,32 @@
+use std::env;
 use std::error::Error;
+use std::process;
 
+#[cfg(feature = "gui")]
 use egui_tetra::StateWrapper;
+#[cfg(feature = "gui")]
 use tetra::ContextBuilder;
 
-use crate::constants::{SCREEN_HEIGHT, SCREEN_WIDTH};
+#[cfg(feature = "gui")]
+use projekt::constants::{SCREEN_HEIGHT, SCREEN_WIDTH};
 
+#[cfg(feature = "gui")]
 pub use crate::game_state::GameState;
 
+#[cfg(feature = "gui")]
 mod camera_handling;
-mod constants;
+mod cli;
+#[cfg(feature = "gui")]
 mod game_state;
-mod graph;
+#[cfg(feature = "gui")]
 mod input;
-mod step_algorithms;
+#[cfg(feature = "gui")]
 mod tetra_handling;
+#[cfg(feature = "gui")]
 mod ui;
 
-fn main() -> Result<(), Box<dyn Error>> {
+#[cfg(feature = "gui")]
+fn open_window() -> Result<(), Box<dyn Error>> {
     ContextBuilder::new("Graph vis", SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32)
         .show_mouse(true)
         .quit_on_escape(true)
@@ -24,3 +34,18 @@ fn main() -> Result<(), Box<dyn Error>> {
         .unwrap()
         .run(|ctx| Ok(StateWrapper::new(GameState::new(ctx))))
 }
+
+#[cfg(not(feature = "gui"))]
+fn open_window() -> Result<(), Box<dyn Error>> {
+    Err(format!("built without the gui feature\n\n{}", cli::USAGE).into())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Any arguments mean batch mode, no window is opened then.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        process::exit(cli::run_cli(&args));
+    }
+
+    open_window()
+}
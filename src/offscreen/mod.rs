@@ -0,0 +1,5 @@
+pub mod recording;
+pub mod renderer;
+
+pub use recording::Recording;
+pub use renderer::OffscreenRenderer;
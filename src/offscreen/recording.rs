@@ -0,0 +1,176 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::Path;
+
+use gif::{Encoder, Frame, Repeat};
+use tiny_skia::Pixmap;
+
+use super::OffscreenRenderer;
+use crate::graph::{drawn_bounds, Graph, Position};
+use crate::step_algorithms::StepAlgorithmResult;
+
+// Lower is better looking and slower, 10 is what the gif crate suggests.
+const GIF_QUANTIZE_SPEED: i32 = 10;
+
+fn union(
+    first: Option<(Position, Position)>,
+    second: Option<(Position, Position)>,
+) -> Option<(Position, Position)> {
+    match (first, second) {
+        (Some((min, max)), Some((other_min, other_max))) => Some((
+            Position::partial_min(min, other_min),
+            Position::partial_max(max, other_max),
+        )),
+        (bounds, None) | (None, bounds) => bounds,
+    }
+}
+
+// Plays an algorithm on a copy of the graph and draws it before the first step and after every
+// step. Nodes don't move in between, so frames only differ in what the steps change.
+pub struct Recording {
+    graph: Graph,
+    directed: bool,
+    result: StepAlgorithmResult,
+    renderer: OffscreenRenderer,
+}
+
+impl Recording {
+    pub fn new(
+        graph: &Graph,
+        directed: bool,
+        mut result: StepAlgorithmResult,
+        width: u32,
+        height: u32,
+    ) -> Recording {
+        let mut graph = graph.clone();
+        result.show_algorithm(&mut graph);
+        result.pause();
+
+        // Annotations show up during the run, the view has room for them from the first frame.
+        let start = drawn_bounds(&graph);
+        result.run_to_end(&mut graph);
+        let bounds = union(start, drawn_bounds(&graph)).unwrap_or_default();
+        result.restart(&mut graph);
+
+        Recording {
+            graph,
+            directed,
+            result,
+            renderer: OffscreenRenderer::new(width, height, bounds),
+        }
+    }
+
+    pub fn renderer_mut(&mut self) -> &mut OffscreenRenderer {
+        &mut self.renderer
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.result.steps().len() + 1
+    }
+
+    pub fn play(
+        mut self,
+        mut on_frame: impl FnMut(&Pixmap) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        loop {
+            let pixmap = self
+                .renderer
+                .render(&self.graph, self.directed)
+                .ok_or("the image size can't be zero")?;
+            on_frame(&pixmap)?;
+            if self.result.is_finished() {
+                return Ok(());
+            }
+            self.result.step_forward(&mut self.graph);
+        }
+    }
+
+    // Writes step_0.png, step_1.png, ... into `dir`, and an animation with `speed` frames per
+    // second if `gif` is given. Returns the number of frames.
+    pub fn save(self, dir: &str, gif: Option<(&str, f32)>) -> Result<usize, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let (width, height) = self.renderer.size();
+        let mut encoder = match gif {
+            Some((path, _)) => {
+                let (width, height) = (u16::try_from(width)?, u16::try_from(height)?);
+                let mut encoder = Encoder::new(File::create(path)?, width, height, &[])?;
+                encoder.set_repeat(Repeat::Infinite)?;
+                Some(encoder)
+            }
+            None => None,
+        };
+        // In hundredths of a second.
+        let delay = gif.map_or(0, |(_, speed)| (100. / speed).round() as u16);
+
+        let digits = (self.frame_count() - 1).to_string().len();
+        let mut count = 0;
+        self.play(|pixmap| {
+            pixmap.save_png(Path::new(dir).join(format!("step_{:0digits$}.png", count)))?;
+            if let Some(encoder) = &mut encoder {
+                // Nothing is transparent, so premultiplied pixels are plain ones.
+                let mut pixels = pixmap.data().to_vec();
+                let mut frame = Frame::from_rgba_speed(
+                    width as u16,
+                    height as u16,
+                    &mut pixels,
+                    GIF_QUANTIZE_SPEED,
+                );
+                frame.delay = delay;
+                encoder.write_frame(&frame)?;
+            }
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use petgraph::graph::NodeIndex;
+
+    use super::Recording;
+    use crate::graph::node::Node;
+    use crate::graph::{Graph, GraphOnCanvas, Position};
+    use crate::step_algorithms::{Bfs, StepAlgorithm};
+
+    #[test]
+    fn one_frame_per_step() {
+        let mut graph = Graph::new();
+        for nr in 0..3 {
+            graph.add_node(Node::new(Position::new(nr as f32 * 80., 0.)));
+        }
+        graph.connect_nodes(NodeIndex::new(0), NodeIndex::new(1));
+        graph.connect_nodes(NodeIndex::new(1), NodeIndex::new(2));
+        let mut bfs = Bfs::from_graph(&graph);
+        bfs.run(&graph, NodeIndex::new(0));
+        let result = bfs.result();
+        let steps = result.steps().len();
+
+        let recording = Recording::new(&graph, true, result, 60, 40);
+        assert_eq!(recording.frame_count(), steps + 1);
+        let mut frames = Vec::new();
+        recording
+            .play(|pixmap| {
+                frames.push(pixmap.clone());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(frames.len(), steps + 1);
+        assert!(frames.windows(2).all(|pair| pair[0] != pair[1]));
+        // The original graph isn't touched.
+        assert!(graph.edge_weights().all(|edge| edge.is_enabled()));
+
+        let dir = std::env::temp_dir().join("projekt_recording_test");
+        let gif = dir.join("run.gif");
+        let recording = Recording::new(&graph, true, Bfs::from_graph(&graph).result(), 60, 40);
+        let count = recording
+            .save(dir.to_str().unwrap(), Some((gif.to_str().unwrap(), 2.)))
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(dir.join("step_0.png").exists());
+        assert!(fs::metadata(gif).unwrap().len() > 0);
+    }
+}
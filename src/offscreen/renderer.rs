@@ -0,0 +1,250 @@
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use tiny_skia::{Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::constants::{
+    ANNOTATION_DISTANCE, BASE_BORDER_SIZE, BASE_RADIUS, BASE_STROKE_WIDTH, FONT_SIZE,
+};
+use crate::graph::edge::Edge;
+use crate::graph::{Color, Graph, GraphOnCanvas, Position};
+
+// Compiled in, so rendering doesn't depend on the directory it's started from.
+static FONT: &[u8] = include_bytes!("../../resources/fonts/JetBrainsMono-Regular.ttf");
+
+fn to_skia_color(color: Color) -> tiny_skia::Color {
+    tiny_skia::Color::from_rgba(
+        color.r.clamp(0., 1.),
+        color.g.clamp(0., 1.),
+        color.b.clamp(0., 1.),
+        color.a.clamp(0., 1.),
+    )
+    .unwrap_or(tiny_skia::Color::BLACK)
+}
+
+fn paint(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(to_skia_color(color));
+    paint.anti_alias = true;
+    paint
+}
+
+fn stroke(width: f32) -> Stroke {
+    Stroke {
+        width,
+        ..Stroke::default()
+    }
+}
+
+// Draws graphs into images without a window, the same way the app does. The view is fixed when
+// the renderer is made, so all images of a recording show the same part of the world.
+pub struct OffscreenRenderer {
+    width: u32,
+    height: u32,
+    // World to pixels.
+    scale: f32,
+    offset: Position,
+    show_weights: bool,
+    font: FontRef<'static>,
+}
+
+impl OffscreenRenderer {
+    // Fits the area between `min` and `max` into the image, keeping its proportions.
+    pub fn new(width: u32, height: u32, (min, max): (Position, Position)) -> OffscreenRenderer {
+        let (min, max) = (min - BASE_RADIUS, max + BASE_RADIUS);
+        let size = max - min;
+        let scale = (width as f32 / size.x).min(height as f32 / size.y);
+        let center = (min + max) / 2.;
+        OffscreenRenderer {
+            width,
+            height,
+            scale,
+            offset: Position::new(width as f32, height as f32) / 2. - center * scale,
+            show_weights: false,
+            font: FontRef::try_from_slice(FONT).unwrap(),
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn set_show_weights(&mut self, show_weights: bool) {
+        self.show_weights = show_weights;
+    }
+
+    fn to_pixels(&self, position: Position) -> Position {
+        position * self.scale + self.offset
+    }
+
+    fn transform(&self) -> Transform {
+        Transform::from_row(self.scale, 0., 0., self.scale, self.offset.x, self.offset.y)
+    }
+
+    // None if the size is zero.
+    pub fn render(&self, graph: &Graph, directed: bool) -> Option<Pixmap> {
+        let mut pixmap = Pixmap::new(self.width, self.height)?;
+        pixmap.fill(tiny_skia::Color::WHITE);
+
+        for idx in graph.edge_indices() {
+            if let (Some((from, to)), Some(edge)) = (graph.edge_line(idx), graph.edge_weight(idx)) {
+                if edge.is_hidden() {
+                    continue;
+                }
+                self.draw_edge(&mut pixmap, edge, from, to, directed);
+                if self.show_weights {
+                    self.draw_text(&mut pixmap, edge.text(), Edge::text_position(from, to));
+                }
+            }
+        }
+
+        for node in graph.node_weights().filter(|node| !node.is_hidden()) {
+            if let Some(circle) =
+                PathBuilder::from_circle(node.position().x, node.position().y, node.radius())
+            {
+                let transform = self.transform();
+                pixmap.fill_path(
+                    &circle,
+                    &paint(node.color()),
+                    tiny_skia::FillRule::Winding,
+                    transform,
+                    None,
+                );
+                pixmap.stroke_path(
+                    &circle,
+                    &paint(node.border_color()),
+                    &stroke(BASE_BORDER_SIZE),
+                    transform,
+                    None,
+                );
+            }
+            self.draw_text(&mut pixmap, node.node_text(), node.position());
+            if let Some(annotation) = node.annotation() {
+                let position = node.position() - Position::new(0., ANNOTATION_DISTANCE);
+                self.draw_text(&mut pixmap, annotation, position);
+            }
+        }
+        Some(pixmap)
+    }
+
+    fn draw_line(&self, pixmap: &mut Pixmap, points: &[Position], color: Color) {
+        let mut builder = PathBuilder::new();
+        for (nr, point) in points.iter().enumerate() {
+            if nr == 0 {
+                builder.move_to(point.x, point.y);
+            } else {
+                builder.line_to(point.x, point.y);
+            }
+        }
+        if let Some(path) = builder.finish() {
+            pixmap.stroke_path(
+                &path,
+                &paint(color),
+                &stroke(BASE_STROKE_WIDTH),
+                self.transform(),
+                None,
+            );
+        }
+    }
+
+    fn draw_edge(
+        &self,
+        pixmap: &mut Pixmap,
+        edge: &Edge,
+        from: Position,
+        to: Position,
+        directed: bool,
+    ) {
+        if !directed {
+            self.draw_line(pixmap, &[from, to], edge.color());
+            return;
+        }
+        let (from, to) = if edge.is_reversed() {
+            (to, from)
+        } else {
+            (from, to)
+        };
+        let (start, tip) = Edge::arrow_line(from, to);
+        let (left, right) = Edge::arrow_arms(from, to);
+        self.draw_line(pixmap, &[start, tip], edge.color());
+        self.draw_line(pixmap, &[left, tip, right], edge.color());
+    }
+
+    // Black, centered on `center`.
+    fn draw_text(&self, pixmap: &mut Pixmap, text: &str, center: Position) {
+        let size = FONT_SIZE * self.scale;
+        let font = self.font.as_scaled(PxScale::from(size));
+        let width: f32 = text
+            .chars()
+            .map(|char| font.h_advance(font.glyph_id(char)))
+            .sum();
+        let center = self.to_pixels(center);
+        let mut x = center.x - width / 2.;
+        let baseline = center.y + (font.ascent() + font.descent()) / 2.;
+
+        let (pixmap_width, pixmap_height) = (pixmap.width() as i32, pixmap.height() as i32);
+        let pixels = pixmap.data_mut();
+        for char in text.chars() {
+            let glyph = font
+                .glyph_id(char)
+                .with_scale_and_position(size, point(x, baseline));
+            x += font.h_advance(glyph.id);
+            let outlined = match self.font.outline_glyph(glyph) {
+                Some(outlined) => outlined,
+                None => continue,
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|glyph_x, glyph_y, coverage| {
+                let x = bounds.min.x as i32 + glyph_x as i32;
+                let y = bounds.min.y as i32 + glyph_y as i32;
+                if x < 0 || y < 0 || x >= pixmap_width || y >= pixmap_height {
+                    return;
+                }
+                // Pixels are premultiplied, darkening the color channels blends in black.
+                let start = ((y * pixmap_width + x) * 4) as usize;
+                for channel in &mut pixels[start..start + 3] {
+                    *channel = (*channel as f32 * (1. - coverage.clamp(0., 1.))).round() as u8;
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OffscreenRenderer;
+    use crate::graph::node::Node;
+    use crate::graph::{drawn_bounds, Color, Graph, GraphOnCanvas, Position};
+
+    #[test]
+    fn draws_into_the_fitted_view() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(Node::new(Position::new(-100., 0.)));
+        let b = graph.add_node(Node::new(Position::new(100., 0.)));
+        graph[b].set_color(Color::GREEN);
+        graph[a].set_node_text("a");
+        graph.connect_nodes(a, b);
+
+        let renderer = OffscreenRenderer::new(200, 100, drawn_bounds(&graph).unwrap());
+        let pixmap = renderer.render(&graph, true).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (200, 100));
+        let pixel = |x: u32, y: u32| {
+            let color = pixmap.pixel(x, y).unwrap();
+            (color.red(), color.green(), color.blue())
+        };
+        // The second node is on the right, the corners are background.
+        let right = renderer.to_pixels(Position::new(100., 0.));
+        assert_eq!(pixel(right.x as u32, right.y as u32), (0, 255, 0));
+        assert_eq!(pixel(0, 0), (255, 255, 255));
+        // The label darkens the middle of the first node.
+        let left = renderer.to_pixels(Position::new(-100., 0.));
+        let label = (-3..=3)
+            .flat_map(|dx| (-3..=3).map(move |dy| (dx, dy)))
+            .filter(|(dx, dy)| {
+                pixel((left.x as i32 + dx) as u32, (left.y as i32 + dy) as u32).0 < 128
+            })
+            .count();
+        assert!(label > 0);
+        // The edge runs between them.
+        let middle = renderer.to_pixels(Position::zero());
+        assert_eq!(pixel(middle.x as u32, middle.y as u32), (0, 0, 0));
+    }
+}
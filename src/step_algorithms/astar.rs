@@ -0,0 +1,354 @@
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use dyn_partial_eq::DynPartialEq;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::Direction;
+use petgraph::EdgeType;
+use serde::{Deserialize, Serialize};
+
+use super::bfs::{NodeState, NodeStep};
+use super::dijkstra::{EdgeState, EdgeStep, QueueEntry};
+use super::step_algorithm::{step_json, EdgeWeight, Step};
+use super::{AlgorithmOutcome, StepAlgorithm, StepAlgorithmResult};
+use crate::constants::BASE_EDGE_WEIGHT;
+use crate::graph::{Graph, Position};
+
+// Lengths have many digits, one after the point is enough to compare scores.
+fn score_text(score: f32) -> String {
+    ((score * 10.).round() / 10.).to_string()
+}
+
+// Cost of the best known path to the node and that plus the estimate to the target.
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ScoreStep {
+    idx: NodeIndex,
+    g: f32,
+    f: f32,
+}
+
+impl ScoreStep {
+    pub fn new(idx: NodeIndex, g: f32, f: f32) -> ScoreStep {
+        ScoreStep { idx, g, f }
+    }
+}
+
+impl Step for ScoreStep {
+    fn apply_step(&self, graph: &mut Graph) {
+        if let Some(node) = graph.node_weight_mut(self.idx) {
+            node.set_annotation(Some(format!(
+                "g {} f {}",
+                score_text(self.g),
+                score_text(self.f)
+            )));
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("astar_score", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.idx]
+    }
+}
+
+// Assumes that weights aren't negative. Open nodes are grey, closed ones green.
+//
+// The heuristic is the straight line to the target on the canvas. In a graph where every edge
+// has the default weight, edges cost their length. Otherwise they cost their weight and the
+// heuristic is scaled down by the smallest weight per length, so it never overestimates.
+pub struct AStar {
+    steps: VecDeque<Box<dyn Step>>,
+    target: NodeIndex,
+    positions: HashMap<NodeIndex, Position>,
+    costs: HashMap<EdgeIndex, f32>,
+    heuristic_scale: f32,
+    g_scores: HashMap<NodeIndex, f32>,
+    // Edge of the best known path into the node.
+    predecessors: HashMap<NodeIndex, (EdgeIndex, NodeIndex)>,
+    expanded: Vec<NodeIndex>,
+    path: Vec<NodeIndex>,
+}
+
+impl StepAlgorithm for AStar {
+    fn run<N, E: EdgeWeight, D: EdgeType>(
+        &mut self,
+        graph: &StableGraph<N, E, D>,
+        start_idx: NodeIndex,
+    ) {
+        self.set_costs(graph);
+        if self.astar(graph, start_idx) {
+            self.show_path(start_idx);
+        }
+    }
+
+    fn result(self) -> StepAlgorithmResult {
+        let distance = self.path.last().map(|idx| self.g_scores[idx]);
+        StepAlgorithmResult::from_steps(self.steps).with_outcome(AlgorithmOutcome::Search {
+            expanded: self.expanded,
+            path: self.path,
+            distance,
+        })
+    }
+}
+
+impl AStar {
+    pub fn from_graph(graph: &Graph, target: NodeIndex) -> AStar {
+        AStar {
+            steps: VecDeque::new(),
+            target,
+            positions: graph
+                .node_indices()
+                .map(|idx| (idx, graph[idx].position()))
+                .collect(),
+            costs: HashMap::new(),
+            heuristic_scale: 1.,
+            g_scores: HashMap::new(),
+            predecessors: HashMap::new(),
+            expanded: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+
+    // What every edge cost the last run.
+    pub fn costs(&self) -> &HashMap<EdgeIndex, f32> {
+        &self.costs
+    }
+
+    fn length<N, E, D: EdgeType>(&self, graph: &StableGraph<N, E, D>, idx: EdgeIndex) -> f32 {
+        let (from, to) = graph.edge_endpoints(idx).unwrap();
+        match (self.positions.get(&from), self.positions.get(&to)) {
+            (Some(from), Some(to)) => from.distance(*to),
+            _ => 0.,
+        }
+    }
+
+    fn set_costs<N, E: EdgeWeight, D: EdgeType>(&mut self, graph: &StableGraph<N, E, D>) {
+        let unweighted = graph
+            .edge_weights()
+            .all(|edge| edge.weight() == BASE_EDGE_WEIGHT);
+        for idx in graph.edge_indices() {
+            let length = self.length(graph, idx);
+            let cost = if unweighted {
+                length
+            } else {
+                graph[idx].weight()
+            };
+            if length > 0. {
+                self.heuristic_scale = self.heuristic_scale.min(cost / length).max(0.);
+            }
+            self.costs.insert(idx, cost);
+        }
+    }
+
+    fn heuristic(&self, idx: NodeIndex) -> f32 {
+        match (self.positions.get(&idx), self.positions.get(&self.target)) {
+            (Some(position), Some(target)) => position.distance(*target) * self.heuristic_scale,
+            _ => 0.,
+        }
+    }
+
+    fn open(&mut self, queue: &mut BinaryHeap<QueueEntry>, idx: NodeIndex, g: f32) {
+        let f = g + self.heuristic(idx);
+        self.g_scores.insert(idx, g);
+        self.steps.push_back(Box::new(ScoreStep::new(idx, g, f)));
+        queue.push(QueueEntry { distance: f, idx });
+    }
+
+    // Returns whether the target was reached.
+    fn astar<N, E, D: EdgeType>(
+        &mut self,
+        graph: &StableGraph<N, E, D>,
+        start_idx: NodeIndex,
+    ) -> bool {
+        let mut queue = BinaryHeap::new();
+        let mut closed = HashSet::new();
+
+        self.open(&mut queue, start_idx, 0.);
+        self.steps
+            .push_back(Box::new(NodeStep::new(start_idx, NodeState::Queued)));
+
+        while let Some(QueueEntry { idx, .. }) = queue.pop() {
+            // Entries left behind by a later improvement.
+            if !closed.insert(idx) {
+                continue;
+            }
+            self.steps
+                .push_back(Box::new(NodeStep::new(idx, NodeState::Visited)));
+            self.expanded.push(idx);
+            if idx == self.target {
+                return true;
+            }
+
+            let g = self.g_scores[&idx];
+            let mut walker = graph.neighbors_directed(idx, Direction::Outgoing).detach();
+            while let Some((edge_idx, other_idx)) = walker.next(graph) {
+                if closed.contains(&other_idx) {
+                    continue;
+                }
+                let new_g = g + self.costs[&edge_idx];
+                let old_g = self.g_scores.get(&other_idx).copied();
+                if old_g.is_some_and(|old_g| old_g <= new_g) {
+                    self.steps
+                        .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Rejected)));
+                    continue;
+                }
+
+                self.steps
+                    .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Relaxed)));
+                if let Some((old_edge_idx, _)) =
+                    self.predecessors.insert(other_idx, (edge_idx, idx))
+                {
+                    self.steps
+                        .push_back(Box::new(EdgeStep::new(old_edge_idx, EdgeState::Rejected)));
+                }
+                self.open(&mut queue, other_idx, new_g);
+                if old_g.is_none() {
+                    self.steps
+                        .push_back(Box::new(NodeStep::new(other_idx, NodeState::Queued)));
+                }
+            }
+        }
+        false
+    }
+
+    fn show_path(&mut self, start_idx: NodeIndex) {
+        let mut idx = self.target;
+        let mut path = Vec::new();
+        self.path.push(idx);
+        while idx != start_idx {
+            match self.predecessors.get(&idx) {
+                Some((edge_idx, previous_idx)) => {
+                    path.push(*edge_idx);
+                    idx = *previous_idx;
+                    self.path.push(idx);
+                }
+                None => break,
+            }
+        }
+        self.path.reverse();
+
+        for edge_idx in path.into_iter().rev() {
+            self.steps
+                .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::OnPath)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+    use petgraph::Undirected;
+
+    use super::AStar;
+    use crate::graph::generators::families::{generate, Family};
+    use crate::graph::node::Node;
+    use crate::graph::{with_edge_type, Graph, GraphOnCanvas, Position};
+    use crate::step_algorithms::{AlgorithmOutcome, Dijkstra, StepAlgorithm};
+
+    fn search(
+        graph: &Graph,
+        astar: AStar,
+        start: NodeIndex,
+    ) -> (Vec<NodeIndex>, Vec<NodeIndex>, Option<f32>) {
+        let mut astar = astar;
+        astar.run(&with_edge_type::<_, _, Undirected>(graph), start);
+        match astar.result().outcome() {
+            AlgorithmOutcome::Search {
+                expanded,
+                path,
+                distance,
+            } => (expanded.clone(), path.clone(), *distance),
+            outcome => panic!("{:?}", outcome),
+        }
+    }
+
+    // Dijkstra with the edge costs A* used, the nodes it expands until the target, its path and
+    // the distance to the target.
+    fn search_like_astar(
+        graph: &Graph,
+        start: NodeIndex,
+        target: NodeIndex,
+    ) -> (usize, Vec<NodeIndex>, f32) {
+        let undirected = with_edge_type::<_, _, Undirected>(graph);
+        let mut astar = AStar::from_graph(graph, target);
+        astar.run(&undirected, start);
+        let mut dijkstra = Dijkstra::new(Some(target)).with_costs(astar.costs().clone());
+        dijkstra.run(&undirected, start);
+        let expanded = dijkstra.expanded_until_target();
+        match dijkstra.result().outcome() {
+            AlgorithmOutcome::Distances { distances, path } => {
+                let distance = distances.iter().find(|(idx, _)| *idx == target).unwrap().1;
+                (expanded, path.clone(), distance)
+            }
+            outcome => panic!("{:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn expands_fewer_nodes_than_dijkstra() {
+        // Along a row of a grid, edges cost their length. Going corner to corner instead would
+        // expand everything, every node in between is as good a guess.
+        let graph = generate(&Family::Grid { rows: 6, cols: 6 });
+        let (start, target) = (NodeIndex::new(12), NodeIndex::new(17));
+        let side = graph[NodeIndex::new(0)]
+            .position()
+            .distance(graph[NodeIndex::new(1)].position());
+
+        let (expanded, path, distance) = search(&graph, AStar::from_graph(&graph, target), start);
+        let (dijkstra_expanded, dijkstra_path, dijkstra_distance) =
+            search_like_astar(&graph, start, target);
+
+        let row = (12..18).map(NodeIndex::new).collect::<Vec<_>>();
+        assert_eq!(path, row);
+        assert_eq!(expanded, row);
+        assert!((distance.unwrap() - 5. * side).abs() < 1e-2);
+        assert_eq!(dijkstra_path, row);
+        assert_eq!(Some(dijkstra_distance), distance);
+        assert!(dijkstra_expanded > 2 * expanded.len());
+    }
+
+    #[test]
+    fn dijkstra_searches_by_length_too() {
+        // The straight way to the target takes three edges, the detour over the top only two.
+        let mut graph = Graph::new();
+        let straight = (0..4)
+            .map(|nr| graph.add_node(Node::new(Position::new(nr as f32 * 100., 0.))))
+            .collect::<Vec<_>>();
+        let top = graph.add_node(Node::new(Position::new(150., 400.)));
+        for pair in straight.windows(2) {
+            graph.connect_nodes(pair[0], pair[1]);
+        }
+        graph.connect_nodes(straight[0], top);
+        graph.connect_nodes(top, straight[3]);
+        let (start, target) = (straight[0], straight[3]);
+
+        let (_, path, distance) = search(&graph, AStar::from_graph(&graph, target), start);
+        let (_, dijkstra_path, dijkstra_distance) = search_like_astar(&graph, start, target);
+        assert_eq!(path, straight);
+        assert_eq!(dijkstra_path, straight);
+        assert!((dijkstra_distance - 300.).abs() < 1e-2);
+        assert_eq!(Some(dijkstra_distance), distance);
+    }
+
+    #[test]
+    fn weights_are_costs_when_set() {
+        // A triangle where the long way round is cheaper.
+        let mut graph = generate(&Family::Cycle { nodes: 3 });
+        let (a, b, c) = (NodeIndex::new(0), NodeIndex::new(1), NodeIndex::new(2));
+        let ac = graph.find_edge(c, a).unwrap();
+        graph[ac].set_weight(10.);
+        let (_, path, distance) = search(&graph, AStar::from_graph(&graph, c), a);
+        assert_eq!(path, vec![a, b, c]);
+        assert_eq!(distance, Some(2.));
+
+        // Unreachable targets leave no path.
+        graph.remove_node(b);
+        graph.remove_edge(ac);
+        let (expanded, path, distance) = search(&graph, AStar::from_graph(&graph, c), a);
+        assert_eq!(expanded, vec![a]);
+        assert!(path.is_empty());
+        assert_eq!(distance, None);
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::graph::Color;
+use dyn_partial_eq::DynPartialEq;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::EdgeType;
+use serde::{Deserialize, Serialize};
+
+use super::bfs::{NodeState, NodeStep};
+use super::dijkstra::{DistanceStep, EdgeState, EdgeStep};
+use super::step_algorithm::{step_json, EdgeWeight, Step};
+use super::{AlgorithmOutcome, StepAlgorithm, StepAlgorithmResult};
+
+const CYCLE_COLOR: Color = Color::rgb(0.6, 0., 0.8);
+
+// Starts a relaxation round: only the edges of the best paths found so far stay shown.
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RoundStep {
+    round: usize,
+    tree: Vec<EdgeIndex>,
+}
+
+impl RoundStep {
+    pub fn new(round: usize, tree: Vec<EdgeIndex>) -> RoundStep {
+        RoundStep { round, tree }
+    }
+}
+
+impl Step for RoundStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        for idx in graph.edge_indices().collect::<Vec<_>>() {
+            let edge = &mut graph[idx];
+            if self.tree.contains(&idx) {
+                edge.enable();
+                edge.set_color(Color::GREEN);
+            } else {
+                edge.disable();
+                edge.set_color(Color::BLACK);
+            }
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("bellman_ford_round", self)
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        self.tree.clone()
+    }
+
+    // Edges outside the tree are hidden again.
+    fn changes_whole_graph(&self) -> bool {
+        true
+    }
+}
+
+// One edge of a negative cycle and the node it leads to.
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CycleStep {
+    edge: EdgeIndex,
+    node: NodeIndex,
+}
+
+impl CycleStep {
+    pub fn new(edge: EdgeIndex, node: NodeIndex) -> CycleStep {
+        CycleStep { edge, node }
+    }
+}
+
+impl Step for CycleStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        if let Some(edge) = graph.edge_weight_mut(self.edge) {
+            edge.enable();
+            edge.set_color(CYCLE_COLOR);
+        }
+        if let Some(node) = graph.node_weight_mut(self.node) {
+            node.set_color(CYCLE_COLOR);
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("bellman_ford_cycle", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.node]
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        vec![self.edge]
+    }
+}
+
+// Weights may be negative. Relaxes every edge once per round, stopping early when a round changes
+// nothing. If edges can still be relaxed after as many rounds as there are nodes minus one,
+// a negative cycle is reachable from the start and is shown instead of the distances.
+pub struct BellmanFord {
+    steps: VecDeque<Box<dyn Step>>,
+    target: Option<NodeIndex>,
+    distances: HashMap<NodeIndex, f32>,
+    // Edge of the best known path into the node.
+    predecessors: HashMap<NodeIndex, (EdgeIndex, NodeIndex)>,
+    path: Vec<NodeIndex>,
+    cycle: Vec<NodeIndex>,
+}
+
+impl StepAlgorithm for BellmanFord {
+    fn run<N, E: EdgeWeight, D: EdgeType>(
+        &mut self,
+        graph: &StableGraph<N, E, D>,
+        start_idx: NodeIndex,
+    ) {
+        match self.bellman_ford(graph, start_idx) {
+            Some(idx) => self.show_cycle(idx, graph.node_count()),
+            None => self.show_path(start_idx),
+        }
+    }
+
+    fn result(self) -> StepAlgorithmResult {
+        let outcome = if self.cycle.is_empty() {
+            let mut distances = self.distances.into_iter().collect::<Vec<_>>();
+            distances.sort_by_key(|(idx, _)| *idx);
+            AlgorithmOutcome::Distances {
+                distances,
+                path: self.path,
+            }
+        } else {
+            AlgorithmOutcome::NegativeCycle { cycle: self.cycle }
+        };
+        StepAlgorithmResult::from_steps(self.steps).with_outcome(outcome)
+    }
+}
+
+impl BellmanFord {
+    // Path to the target is highlighted once all distances are known.
+    pub fn new(target: Option<NodeIndex>) -> BellmanFord {
+        BellmanFord {
+            steps: VecDeque::new(),
+            target,
+            distances: HashMap::new(),
+            predecessors: HashMap::new(),
+            path: Vec::new(),
+            cycle: Vec::new(),
+        }
+    }
+
+    fn tree(&self) -> Vec<EdgeIndex> {
+        let mut tree = self
+            .predecessors
+            .values()
+            .map(|(edge_idx, _)| *edge_idx)
+            .collect::<Vec<_>>();
+        tree.sort();
+        tree
+    }
+
+    // Returns a node whose distance still improves after the last round, if there is one.
+    fn bellman_ford<N, E: EdgeWeight, D: EdgeType>(
+        &mut self,
+        graph: &StableGraph<N, E, D>,
+        start_idx: NodeIndex,
+    ) -> Option<NodeIndex> {
+        // Undirected edges can be relaxed both ways.
+        let arcs = graph
+            .edge_indices()
+            .flat_map(|idx| {
+                let (from, to) = graph.edge_endpoints(idx).unwrap();
+                let weight = graph[idx].weight();
+                let back = (!D::is_directed()).then_some((idx, to, from, weight));
+                [Some((idx, from, to, weight)), back]
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+
+        self.distances.insert(start_idx, 0.);
+        self.steps
+            .push_back(Box::new(DistanceStep::new(start_idx, 0.)));
+        self.steps
+            .push_back(Box::new(NodeStep::new(start_idx, NodeState::Visited)));
+
+        for round in 1..graph.node_count() {
+            self.steps
+                .push_back(Box::new(RoundStep::new(round, self.tree())));
+            let mut changed = false;
+            for &(edge_idx, from, to, weight) in &arcs {
+                let Some(distance) = self.distances.get(&from).copied() else {
+                    continue;
+                };
+                let new_distance = distance + weight;
+                if self
+                    .distances
+                    .get(&to)
+                    .is_some_and(|old_distance| *old_distance <= new_distance)
+                {
+                    // Edges already on the best paths stay as they are.
+                    if self.predecessors.get(&to) != Some(&(edge_idx, from)) {
+                        self.steps
+                            .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Rejected)));
+                    }
+                    continue;
+                }
+
+                self.steps
+                    .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Relaxed)));
+                if let Some((old_edge_idx, _)) = self.predecessors.insert(to, (edge_idx, from)) {
+                    if old_edge_idx != edge_idx {
+                        self.steps
+                            .push_back(Box::new(EdgeStep::new(old_edge_idx, EdgeState::Rejected)));
+                    }
+                }
+                self.steps
+                    .push_back(Box::new(DistanceStep::new(to, new_distance)));
+                self.distances.insert(to, new_distance);
+                changed = true;
+            }
+            if !changed {
+                return None;
+            }
+        }
+
+        let (edge_idx, from, to, _) = arcs.into_iter().find(|&(_, from, to, weight)| {
+            match (self.distances.get(&from), self.distances.get(&to)) {
+                (Some(distance), Some(old_distance)) => distance + weight < *old_distance,
+                _ => false,
+            }
+        })?;
+        self.predecessors.insert(to, (edge_idx, from));
+        Some(to)
+    }
+
+    // `idx` is behind the cycle or on it, enough steps back along the predecessors end up on it.
+    fn show_cycle(&mut self, mut idx: NodeIndex, node_count: usize) {
+        for _ in 0..node_count {
+            idx = self.predecessors[&idx].1;
+        }
+
+        let mut edges = Vec::new();
+        let mut seen = HashSet::new();
+        while seen.insert(idx) {
+            let (edge_idx, previous_idx) = self.predecessors[&idx];
+            edges.push((edge_idx, idx));
+            idx = previous_idx;
+        }
+        edges.reverse();
+
+        self.cycle = edges.iter().map(|(_, idx)| *idx).collect();
+        self.steps
+            .push_back(Box::new(RoundStep::new(node_count, Vec::new())));
+        for (edge_idx, idx) in edges {
+            self.steps
+                .push_back(Box::new(CycleStep::new(edge_idx, idx)));
+        }
+    }
+
+    fn show_path(&mut self, start_idx: NodeIndex) {
+        let mut idx = match self.target {
+            Some(target) if self.distances.contains_key(&target) => target,
+            _ => return,
+        };
+
+        let mut path = Vec::new();
+        self.path.push(idx);
+        while idx != start_idx {
+            match self.predecessors.get(&idx) {
+                Some((edge_idx, previous_idx)) => {
+                    path.push(*edge_idx);
+                    idx = *previous_idx;
+                    self.path.push(idx);
+                }
+                None => break,
+            }
+        }
+        self.path.reverse();
+
+        for edge_idx in path.into_iter().rev() {
+            self.steps
+                .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::OnPath)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use petgraph::stable_graph::StableGraph;
+    use petgraph::{Directed, Undirected};
+
+    use super::{BellmanFord, RoundStep};
+    use crate::step_algorithms::bfs::{NodeState, NodeStep};
+    use crate::step_algorithms::dijkstra::{DistanceStep, EdgeState, EdgeStep};
+    use crate::step_algorithms::{step_algorithm::Step, AlgorithmOutcome, StepAlgorithm};
+
+    #[test]
+    fn negative_edge_without_cycle() {
+        let mut graph = StableGraph::<u32, i32, Directed, u32>::new();
+        let a = graph.add_node(0);
+        let b = graph.add_node(1);
+        let c = graph.add_node(2);
+        let bc = graph.add_edge(b, c, -3);
+        let ab = graph.add_edge(a, b, 4);
+        let ac = graph.add_edge(a, c, 2);
+        let mut bellman_ford = BellmanFord::new(Some(c));
+        bellman_ford.run(&graph, a);
+        let res = bellman_ford.result();
+
+        let mut desired = VecDeque::<Box<dyn Step>>::new();
+        desired.push_back(Box::new(DistanceStep::new(a, 0.)));
+        desired.push_back(Box::new(NodeStep::new(a, NodeState::Visited)));
+        // b isn't reached yet when b -> c is looked at.
+        desired.push_back(Box::new(RoundStep::new(1, Vec::new())));
+        desired.push_back(Box::new(EdgeStep::new(ab, EdgeState::Relaxed)));
+        desired.push_back(Box::new(DistanceStep::new(b, 4.)));
+        desired.push_back(Box::new(EdgeStep::new(ac, EdgeState::Relaxed)));
+        desired.push_back(Box::new(DistanceStep::new(c, 2.)));
+        desired.push_back(Box::new(RoundStep::new(2, vec![ab, ac])));
+        desired.push_back(Box::new(EdgeStep::new(bc, EdgeState::Relaxed)));
+        desired.push_back(Box::new(EdgeStep::new(ac, EdgeState::Rejected)));
+        desired.push_back(Box::new(DistanceStep::new(c, 1.)));
+        desired.push_back(Box::new(EdgeStep::new(ac, EdgeState::Rejected)));
+        desired.push_back(Box::new(EdgeStep::new(ab, EdgeState::OnPath)));
+        desired.push_back(Box::new(EdgeStep::new(bc, EdgeState::OnPath)));
+        assert_eq!(res.steps(), &desired);
+
+        assert_eq!(
+            res.outcome(),
+            &AlgorithmOutcome::Distances {
+                distances: vec![(a, 0.), (b, 4.), (c, 1.)],
+                path: vec![a, b, c],
+            }
+        );
+    }
+
+    #[test]
+    fn finds_negative_cycle() {
+        let mut graph = StableGraph::<u32, i32, Directed, u32>::new();
+        let nodes = (0..4).map(|nr| graph.add_node(nr)).collect::<Vec<_>>();
+        graph.add_edge(nodes[0], nodes[1], 1);
+        let cycle_edges = [
+            graph.add_edge(nodes[1], nodes[2], 1),
+            graph.add_edge(nodes[2], nodes[3], -4),
+            graph.add_edge(nodes[3], nodes[1], 2),
+        ];
+        let mut bellman_ford = BellmanFord::new(Some(nodes[3]));
+        bellman_ford.run(&graph, nodes[0]);
+        let res = bellman_ford.result();
+
+        let cycle = match res.outcome() {
+            AlgorithmOutcome::NegativeCycle { cycle } => cycle.clone(),
+            outcome => panic!("{:?}", outcome),
+        };
+        assert_eq!(cycle.len(), 3);
+        let mut sorted = cycle.clone();
+        sorted.sort();
+        assert_eq!(sorted, nodes[1..]);
+
+        // The cycle is painted edge by edge in its direction, after everything else.
+        let painted = res.steps().iter().skip(res.steps().len() - 3);
+        for (step, idx) in painted.zip(&cycle) {
+            assert_eq!(step.to_json()["step"], "bellman_ford_cycle");
+            assert_eq!(step.nodes(), vec![*idx]);
+            let edge_idx = step.edges()[0];
+            assert!(cycle_edges.contains(&edge_idx));
+            assert_eq!(graph.edge_endpoints(edge_idx).unwrap().1, *idx);
+        }
+    }
+
+    #[test]
+    fn negative_undirected_edge_is_a_cycle() {
+        let mut graph = StableGraph::<u32, i32, Undirected, u32>::default();
+        let a = graph.add_node(0);
+        let b = graph.add_node(1);
+        let c = graph.add_node(2);
+        graph.add_edge(a, b, 2);
+        graph.add_edge(b, c, -1);
+        let mut bellman_ford = BellmanFord::new(None);
+        bellman_ford.run(&graph, a);
+
+        match bellman_ford.result().outcome() {
+            AlgorithmOutcome::NegativeCycle { cycle } => assert_eq!(cycle.len(), 2),
+            outcome => panic!("{:?}", outcome),
+        }
+    }
+}
@@ -1,26 +1,29 @@
 use std::collections::HashMap;
 use std::collections::VecDeque;
 
+use crate::graph::Color;
 use dyn_partial_eq::DynPartialEq;
 use petgraph::graph::EdgeIndex;
 use petgraph::graph::NodeIndex;
+use petgraph::stable_graph::StableGraph;
 use petgraph::Direction;
 use petgraph::EdgeType;
-use petgraph::Graph;
-use tetra::graphics::Color;
+use serde::{Deserialize, Serialize};
 
+use super::AlgorithmOutcome;
 use super::StepAlgorithm;
 use super::StepAlgorithmResult;
-use crate::step_algorithms::step_algorithm::Step;
+use crate::step_algorithms::step_algorithm::{step_json, Step};
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NodeState {
     Visited,
     Queued,
     NotVisited,
 }
 
-#[derive(DynPartialEq, PartialEq, Debug)]
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct NodeStep {
     idx: NodeIndex,
     to_state: NodeState,
@@ -42,9 +45,17 @@ impl Step for NodeStep {
             });
         }
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("bfs_node", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.idx]
+    }
 }
 
-#[derive(DynPartialEq, PartialEq, Debug)]
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct EdgeStep {
     idx: EdgeIndex,
 }
@@ -61,25 +72,35 @@ impl Step for EdgeStep {
             edge.enable();
         }
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("bfs_edge", self)
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        vec![self.idx]
+    }
 }
 
 pub struct Bfs {
     steps: VecDeque<Box<dyn Step>>,
     states: HashMap<NodeIndex, NodeState>,
+    order: Vec<NodeIndex>,
 }
 
 impl StepAlgorithm for Bfs {
-    fn run<N, E, D: EdgeType>(&mut self, graph: &Graph<N, E, D>, start_idx: NodeIndex) {
+    fn run<N, E, D: EdgeType>(&mut self, graph: &StableGraph<N, E, D>, start_idx: NodeIndex) {
         self.bfs(graph, start_idx);
     }
 
     fn result(self) -> StepAlgorithmResult {
         StepAlgorithmResult::from_steps(self.steps)
+            .with_outcome(AlgorithmOutcome::Order { order: self.order })
     }
 }
 
 impl Bfs {
-    pub fn from_graph<N, E, D: EdgeType>(graph: &Graph<N, E, D>) -> Bfs {
+    pub fn from_graph<N, E, D: EdgeType>(graph: &StableGraph<N, E, D>) -> Bfs {
         let mut states = HashMap::new();
         for index in graph.node_indices() {
             states.insert(index, NodeState::NotVisited);
@@ -87,10 +108,11 @@ impl Bfs {
         Bfs {
             steps: VecDeque::new(),
             states,
+            order: Vec::new(),
         }
     }
 
-    fn bfs<N, E, D: EdgeType>(&mut self, graph: &Graph<N, E, D>, start_idx: NodeIndex) {
+    fn bfs<N, E, D: EdgeType>(&mut self, graph: &StableGraph<N, E, D>, start_idx: NodeIndex) {
         let mut q = VecDeque::<NodeIndex>::new();
         q.push_back(start_idx);
         self.steps
@@ -112,6 +134,7 @@ impl Bfs {
                 }
             }
             self.states.insert(idx, NodeState::Visited);
+            self.order.push(idx);
             self.steps
                 .push_back(Box::new(NodeStep::new(idx, NodeState::Visited)));
         }
@@ -120,6 +143,8 @@ impl Bfs {
 
 #[cfg(test)]
 mod tests {
+    use petgraph::stable_graph::StableGraph;
+
     use super::Bfs;
     use crate::step_algorithms::{
         bfs::{EdgeStep, NodeState, NodeStep},
@@ -130,7 +155,7 @@ mod tests {
 
     #[test]
     fn small_test() {
-        let mut graph = petgraph::Graph::<u32, u32, petgraph::Directed, u32>::new();
+        let mut graph = StableGraph::<u32, u32, petgraph::Directed, u32>::new();
         let a = graph.add_node(1);
         let b = graph.add_node(2);
         let edge_idx = graph.add_edge(a, b, 0);
@@ -0,0 +1,262 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph::Color;
+use dyn_partial_eq::DynPartialEq;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::Undirected;
+use serde::{Deserialize, Serialize};
+
+use super::bfs::{NodeState, NodeStep};
+use super::scc::PaintComponent;
+use super::step_algorithm::{step_json, EdgeWeight, Step};
+use super::{AlgorithmOutcome, StepAlgorithmResult, UndirectedStepAlgorithm};
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeState {
+    // Leads to a node the search hadn't found yet.
+    Tree,
+    // Leads back to a node higher up in the search tree.
+    Back,
+    Bridge,
+}
+
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EdgeStep {
+    idx: EdgeIndex,
+    to_state: EdgeState,
+}
+
+impl EdgeStep {
+    pub fn new(idx: EdgeIndex, to_state: EdgeState) -> EdgeStep {
+        EdgeStep { idx, to_state }
+    }
+}
+
+impl Step for EdgeStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        if let Some(edge) = graph.edge_weight_mut(self.idx) {
+            edge.enable();
+            edge.set_color(match self.to_state {
+                EdgeState::Tree => Color::GREEN,
+                EdgeState::Back => Color::BLUE,
+                EdgeState::Bridge => Color::RED,
+            });
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("biconnected_edge", self)
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        vec![self.idx]
+    }
+}
+
+// Discovery time and low-link value, shown as "disc/low".
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LowLinkStep {
+    idx: NodeIndex,
+    disc: usize,
+    low: usize,
+}
+
+impl LowLinkStep {
+    pub fn new(idx: NodeIndex, disc: usize, low: usize) -> LowLinkStep {
+        LowLinkStep { idx, disc, low }
+    }
+}
+
+impl Step for LowLinkStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        if let Some(node) = graph.node_weight_mut(self.idx) {
+            node.set_annotation(Some(format!("{}/{}", self.disc, self.low)));
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("biconnected_low_link", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.idx]
+    }
+}
+
+// Articulation points and bridges with Tarjan's low-link values. Searches from the start node
+// first, then from every node it didn't reach. Articulation points are painted red at the end.
+pub struct Biconnectivity {
+    steps: VecDeque<Box<dyn Step>>,
+    disc: HashMap<NodeIndex, usize>,
+    low: HashMap<NodeIndex, usize>,
+    articulation_points: Vec<NodeIndex>,
+    bridges: Vec<EdgeIndex>,
+}
+
+impl UndirectedStepAlgorithm for Biconnectivity {
+    fn run<N, E: EdgeWeight>(
+        &mut self,
+        graph: &StableGraph<N, E, Undirected>,
+        start_idx: NodeIndex,
+    ) {
+        let roots = std::iter::once(start_idx).chain(graph.node_indices());
+        for idx in roots {
+            if graph.contains_node(idx) && !self.disc.contains_key(&idx) {
+                self.search(graph, idx, None);
+            }
+        }
+
+        for idx in self.bridges.iter() {
+            self.steps
+                .push_back(Box::new(EdgeStep::new(*idx, EdgeState::Bridge)));
+        }
+        if !self.articulation_points.is_empty() {
+            self.steps.push_back(Box::new(PaintComponent::new(
+                Color::RED,
+                self.articulation_points.clone(),
+            )));
+        }
+    }
+
+    fn result(mut self) -> StepAlgorithmResult {
+        self.articulation_points.sort();
+        self.bridges.sort();
+        StepAlgorithmResult::from_steps(self.steps).with_outcome(AlgorithmOutcome::Biconnectivity {
+            articulation_points: self.articulation_points,
+            bridges: self.bridges,
+        })
+    }
+}
+
+impl Biconnectivity {
+    pub fn new() -> Biconnectivity {
+        Biconnectivity {
+            steps: VecDeque::new(),
+            disc: HashMap::new(),
+            low: HashMap::new(),
+            articulation_points: Vec::new(),
+            bridges: Vec::new(),
+        }
+    }
+
+    fn set_low(&mut self, idx: NodeIndex, low: usize) {
+        if low < self.low[&idx] {
+            self.low.insert(idx, low);
+            self.steps
+                .push_back(Box::new(LowLinkStep::new(idx, self.disc[&idx], low)));
+        }
+    }
+
+    // The edge the search came in by is skipped, so a parallel edge still counts as a way back.
+    fn search<N, E>(
+        &mut self,
+        graph: &StableGraph<N, E, Undirected>,
+        idx: NodeIndex,
+        parent_edge: Option<EdgeIndex>,
+    ) {
+        let disc = self.disc.len();
+        self.disc.insert(idx, disc);
+        self.low.insert(idx, disc);
+        self.steps
+            .push_back(Box::new(NodeStep::new(idx, NodeState::Queued)));
+        self.steps
+            .push_back(Box::new(LowLinkStep::new(idx, disc, disc)));
+
+        let mut children = 0;
+        let mut is_articulation_point = false;
+        let mut walker = graph.neighbors(idx).detach();
+        while let Some((edge_idx, other_idx)) = walker.next(graph) {
+            if Some(edge_idx) == parent_edge {
+                continue;
+            }
+            match self.disc.get(&other_idx).copied() {
+                // Seen from the other end already, or a self loop.
+                Some(other_disc) if other_disc >= disc => {}
+                Some(other_disc) => {
+                    self.steps
+                        .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Back)));
+                    self.set_low(idx, other_disc);
+                }
+                None => {
+                    children += 1;
+                    self.steps
+                        .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Tree)));
+                    self.search(graph, other_idx, Some(edge_idx));
+
+                    let other_low = self.low[&other_idx];
+                    self.set_low(idx, other_low);
+                    // Nothing below the edge reaches above it.
+                    if other_low > disc {
+                        self.bridges.push(edge_idx);
+                    }
+                    if other_low >= disc && parent_edge.is_some() {
+                        is_articulation_point = true;
+                    }
+                }
+            }
+        }
+
+        // The root of a search only separates its subtrees from each other.
+        if is_articulation_point || (parent_edge.is_none() && children > 1) {
+            self.articulation_points.push(idx);
+        }
+        self.steps
+            .push_back(Box::new(NodeStep::new(idx, NodeState::Visited)));
+    }
+}
+
+impl Default for Biconnectivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::{EdgeIndex, NodeIndex};
+    use petgraph::stable_graph::StableGraph;
+
+    use super::Biconnectivity;
+    use crate::step_algorithms::{AlgorithmOutcome, UndirectedStepAlgorithm};
+
+    fn run(edges: &[(usize, usize)], start: usize) -> AlgorithmOutcome {
+        let mut graph = StableGraph::<u32, u32, petgraph::Undirected>::default();
+        for nr in 0..6 {
+            graph.add_node(nr);
+        }
+        for (from, to) in edges {
+            graph.add_edge(NodeIndex::new(*from), NodeIndex::new(*to), 1);
+        }
+        let mut biconnectivity = Biconnectivity::new();
+        biconnectivity.run(&graph, NodeIndex::new(start));
+        biconnectivity.result().outcome().clone()
+    }
+
+    fn outcome(points: &[usize], bridges: &[usize]) -> AlgorithmOutcome {
+        AlgorithmOutcome::Biconnectivity {
+            articulation_points: points.iter().map(|nr| NodeIndex::new(*nr)).collect(),
+            bridges: bridges.iter().map(|nr| EdgeIndex::new(*nr)).collect(),
+        }
+    }
+
+    #[test]
+    fn two_triangles_joined_by_a_bridge() {
+        // Triangles 0 1 2 and 3 4 5, the edge 2 - 3 holds them together.
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3)];
+
+        for start in 0..6 {
+            assert_eq!(run(&edges, start), outcome(&[2, 3], &[3]));
+        }
+    }
+
+    #[test]
+    fn parallel_edges_are_no_bridge() {
+        // A path 0 - 1 - 2 with the first edge doubled, and 3 - 4 apart from it.
+        let edges = [(0, 1), (1, 0), (1, 2), (3, 4)];
+
+        assert_eq!(run(&edges, 0), outcome(&[1], &[2, 3]));
+        assert_eq!(run(&edges, 2), outcome(&[1], &[2, 3]));
+    }
+}
@@ -1,26 +1,29 @@
 use std::collections::HashMap;
 use std::collections::VecDeque;
 
+use crate::graph::Color;
 use dyn_partial_eq::DynPartialEq;
 use petgraph::graph::EdgeIndex;
 use petgraph::graph::NodeIndex;
+use petgraph::stable_graph::StableGraph;
 use petgraph::Direction;
 use petgraph::EdgeType;
-use petgraph::Graph;
-use tetra::graphics::Color;
+use serde::{Deserialize, Serialize};
 
+use super::AlgorithmOutcome;
 use super::StepAlgorithm;
 use super::StepAlgorithmResult;
-use crate::step_algorithms::step_algorithm::Step;
+use crate::step_algorithms::step_algorithm::{step_json, Step};
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NodeState {
     Visited,
     Queued,
     NotVisited,
 }
 
-#[derive(DynPartialEq, PartialEq, Debug)]
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct NodeStep {
     idx: NodeIndex,
     to_state: NodeState,
@@ -42,9 +45,17 @@ impl Step for NodeStep {
             });
         }
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("dfs_node", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.idx]
+    }
 }
 
-#[derive(DynPartialEq, PartialEq, Debug)]
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct EdgeStep {
     idx: EdgeIndex,
 }
@@ -61,6 +72,14 @@ impl Step for EdgeStep {
             edge.enable();
         }
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("dfs_edge", self)
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        vec![self.idx]
+    }
 }
 
 pub struct Dfs {
@@ -71,12 +90,14 @@ pub struct Dfs {
 }
 
 impl StepAlgorithm for Dfs {
-    fn run<N, E, D: EdgeType>(&mut self, graph: &Graph<N, E, D>, start_idx: NodeIndex) {
+    fn run<N, E, D: EdgeType>(&mut self, graph: &StableGraph<N, E, D>, start_idx: NodeIndex) {
         self.dfs(graph, start_idx);
     }
 
     fn result(self) -> StepAlgorithmResult {
+        let order = self.preorder.clone();
         StepAlgorithmResult::from_steps(self.into_steps())
+            .with_outcome(AlgorithmOutcome::Order { order })
     }
 }
 
@@ -105,7 +126,7 @@ impl Dfs {
         &mut self.steps
     }
 
-    pub fn from_graph<N, E, D: EdgeType>(graph: &Graph<N, E, D>) -> Dfs {
+    pub fn from_graph<N, E, D: EdgeType>(graph: &StableGraph<N, E, D>) -> Dfs {
         let mut states = HashMap::new();
         for index in graph.node_indices() {
             states.insert(index, NodeState::NotVisited);
@@ -118,13 +139,13 @@ impl Dfs {
         }
     }
 
-    fn dfs<N, E, D: EdgeType>(&mut self, graph: &Graph<N, E, D>, node_index: NodeIndex) {
+    fn dfs<N, E, D: EdgeType>(&mut self, graph: &StableGraph<N, E, D>, node_index: NodeIndex) {
         self.dfs_helper(graph, node_index, Direction::Outgoing);
     }
 
     pub fn dfs_reversed<N, E, D: EdgeType>(
         &mut self,
-        graph: &Graph<N, E, D>,
+        graph: &StableGraph<N, E, D>,
         node_index: NodeIndex,
     ) {
         self.dfs_helper(graph, node_index, Direction::Incoming);
@@ -132,7 +153,7 @@ impl Dfs {
 
     fn dfs_helper<N, E, D: EdgeType>(
         &mut self,
-        graph: &Graph<N, E, D>,
+        graph: &StableGraph<N, E, D>,
         node_index: NodeIndex,
         direction: Direction,
     ) {
@@ -163,17 +184,20 @@ impl Dfs {
 
 #[cfg(test)]
 mod tests {
+    use petgraph::stable_graph::StableGraph;
     use petgraph::EdgeType;
 
     use super::Dfs;
     use crate::step_algorithms::{
         dfs::{EdgeStep, NodeState, NodeStep},
         step_algorithm::Step,
-        StepAlgorithm,
+        EdgeWeight, StepAlgorithm,
     };
     use std::collections::VecDeque;
 
-    fn small_test_main<N: Default, E: Default, D: EdgeType>(mut graph: petgraph::Graph<N, E, D>) {
+    fn small_test_main<N: Default, E: Default + EdgeWeight, D: EdgeType>(
+        mut graph: StableGraph<N, E, D>,
+    ) {
         let a = graph.add_node(N::default());
         let b = graph.add_node(N::default());
         let edge_idx = graph.add_edge(a, b, E::default());
@@ -195,13 +219,13 @@ mod tests {
 
     #[test]
     fn small_test_directed() {
-        let graph = petgraph::Graph::<u32, u32, petgraph::Directed>::new();
+        let graph = StableGraph::<u32, u32, petgraph::Directed>::new();
         small_test_main(graph);
     }
 
     #[test]
     fn small_test_undirected() {
-        let graph = petgraph::Graph::<u32, u32, petgraph::Undirected>::new_undirected();
+        let graph = StableGraph::<u32, u32, petgraph::Undirected>::default();
         small_test_main(graph);
     }
 }
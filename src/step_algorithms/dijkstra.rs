@@ -0,0 +1,345 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use crate::graph::Color;
+use dyn_partial_eq::DynPartialEq;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::Direction;
+use petgraph::EdgeType;
+use serde::{Deserialize, Serialize};
+
+use super::bfs::{NodeState, NodeStep};
+use super::step_algorithm::{step_json, EdgeWeight, Step};
+use super::{AlgorithmOutcome, StepAlgorithm, StepAlgorithmResult};
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeState {
+    Relaxed,
+    Rejected,
+    OnPath,
+}
+
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EdgeStep {
+    idx: EdgeIndex,
+    to_state: EdgeState,
+}
+
+impl EdgeStep {
+    pub fn new(idx: EdgeIndex, to_state: EdgeState) -> EdgeStep {
+        EdgeStep { idx, to_state }
+    }
+}
+
+impl Step for EdgeStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        if let Some(edge) = graph.edge_weight_mut(self.idx) {
+            edge.enable();
+            edge.set_color(match self.to_state {
+                EdgeState::Relaxed => Color::GREEN,
+                EdgeState::Rejected => Color::RED,
+                EdgeState::OnPath => Color::BLUE,
+            });
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("dijkstra_edge", self)
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        vec![self.idx]
+    }
+}
+
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct DistanceStep {
+    idx: NodeIndex,
+    distance: f32,
+}
+
+impl DistanceStep {
+    pub fn new(idx: NodeIndex, distance: f32) -> DistanceStep {
+        DistanceStep { idx, distance }
+    }
+}
+
+impl Step for DistanceStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        if let Some(node) = graph.node_weight_mut(self.idx) {
+            node.set_annotation(Some(self.distance.to_string()));
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("dijkstra_distance", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.idx]
+    }
+}
+
+// Min-heap entry, ties go to the smaller index so the steps don't depend on the heap.
+#[derive(PartialEq)]
+pub(super) struct QueueEntry {
+    pub(super) distance: f32,
+    pub(super) idx: NodeIndex,
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.idx.cmp(&self.idx))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Assumes that weights aren't negative.
+pub struct Dijkstra {
+    steps: VecDeque<Box<dyn Step>>,
+    target: Option<NodeIndex>,
+    distances: HashMap<NodeIndex, f32>,
+    // Edge of the best known path into the node.
+    predecessors: HashMap<NodeIndex, (EdgeIndex, NodeIndex)>,
+    // Nodes in the order they were taken from the queue.
+    extracted: Vec<NodeIndex>,
+    // Replaces the weight of an edge.
+    costs: HashMap<EdgeIndex, f32>,
+    path: Vec<NodeIndex>,
+}
+
+impl StepAlgorithm for Dijkstra {
+    fn run<N, E: EdgeWeight, D: EdgeType>(
+        &mut self,
+        graph: &StableGraph<N, E, D>,
+        start_idx: NodeIndex,
+    ) {
+        self.dijkstra(graph, start_idx);
+        self.show_path(start_idx);
+    }
+
+    fn result(self) -> StepAlgorithmResult {
+        let mut distances = self.distances.into_iter().collect::<Vec<_>>();
+        distances.sort_by_key(|(idx, _)| *idx);
+        StepAlgorithmResult::from_steps(self.steps).with_outcome(AlgorithmOutcome::Distances {
+            distances,
+            path: self.path,
+        })
+    }
+}
+
+impl Dijkstra {
+    // Path to the target is highlighted once all distances are known.
+    pub fn new(target: Option<NodeIndex>) -> Dijkstra {
+        Dijkstra {
+            steps: VecDeque::new(),
+            target,
+            distances: HashMap::new(),
+            predecessors: HashMap::new(),
+            extracted: Vec::new(),
+            costs: HashMap::new(),
+            path: Vec::new(),
+        }
+    }
+
+    // Edges cost what `costs` says instead of their weight, to search the same problem as A*.
+    pub fn with_costs(mut self, costs: HashMap<EdgeIndex, f32>) -> Dijkstra {
+        self.costs = costs;
+        self
+    }
+
+    // Nodes taken from the queue up to and including the target, as many as a search that
+    // stops there expands. All of them without a reachable target.
+    pub fn expanded_until_target(&self) -> usize {
+        match self.target {
+            Some(target) => match self.extracted.iter().position(|idx| *idx == target) {
+                Some(place) => place + 1,
+                None => self.extracted.len(),
+            },
+            None => self.extracted.len(),
+        }
+    }
+
+    fn dijkstra<N, E: EdgeWeight, D: EdgeType>(
+        &mut self,
+        graph: &StableGraph<N, E, D>,
+        start_idx: NodeIndex,
+    ) {
+        let mut queue = BinaryHeap::new();
+        let mut extracted = HashSet::new();
+
+        self.distances.insert(start_idx, 0.);
+        queue.push(QueueEntry {
+            distance: 0.,
+            idx: start_idx,
+        });
+        self.steps
+            .push_back(Box::new(DistanceStep::new(start_idx, 0.)));
+        self.steps
+            .push_back(Box::new(NodeStep::new(start_idx, NodeState::Queued)));
+
+        while let Some(QueueEntry { distance, idx }) = queue.pop() {
+            // Entries left behind by a later improvement.
+            if !extracted.insert(idx) {
+                continue;
+            }
+            self.extracted.push(idx);
+            self.steps
+                .push_back(Box::new(NodeStep::new(idx, NodeState::Visited)));
+
+            let mut walker = graph.neighbors_directed(idx, Direction::Outgoing).detach();
+            while let Some((edge_idx, other_idx)) = walker.next(graph) {
+                if extracted.contains(&other_idx) {
+                    continue;
+                }
+                let cost = match self.costs.get(&edge_idx) {
+                    Some(cost) => *cost,
+                    None => graph[edge_idx].weight(),
+                };
+                let new_distance = distance + cost;
+                let old_distance = self.distances.get(&other_idx).copied();
+                if old_distance.is_some_and(|old_distance| old_distance <= new_distance) {
+                    self.steps
+                        .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Rejected)));
+                    continue;
+                }
+
+                self.steps
+                    .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Relaxed)));
+                // The edge that led here before is no longer on the best path.
+                if let Some((old_edge_idx, _)) =
+                    self.predecessors.insert(other_idx, (edge_idx, idx))
+                {
+                    self.steps
+                        .push_back(Box::new(EdgeStep::new(old_edge_idx, EdgeState::Rejected)));
+                }
+                self.steps
+                    .push_back(Box::new(DistanceStep::new(other_idx, new_distance)));
+                if old_distance.is_none() {
+                    self.steps
+                        .push_back(Box::new(NodeStep::new(other_idx, NodeState::Queued)));
+                }
+
+                self.distances.insert(other_idx, new_distance);
+                queue.push(QueueEntry {
+                    distance: new_distance,
+                    idx: other_idx,
+                });
+            }
+        }
+    }
+
+    fn show_path(&mut self, start_idx: NodeIndex) {
+        let mut idx = match self.target {
+            Some(target) if self.distances.contains_key(&target) => target,
+            _ => return,
+        };
+
+        let mut path = Vec::new();
+        self.path.push(idx);
+        while idx != start_idx {
+            match self.predecessors.get(&idx) {
+                Some((edge_idx, previous_idx)) => {
+                    path.push(*edge_idx);
+                    idx = *previous_idx;
+                    self.path.push(idx);
+                }
+                None => break,
+            }
+        }
+        self.path.reverse();
+
+        for edge_idx in path.into_iter().rev() {
+            self.steps
+                .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::OnPath)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::{DistanceStep, EdgeState, EdgeStep};
+    use crate::step_algorithms::bfs::{NodeState, NodeStep};
+    use crate::step_algorithms::{step_algorithm::Step, Dijkstra, StepAlgorithm};
+    use std::collections::VecDeque;
+
+    #[test]
+    fn small_test() {
+        let mut graph = StableGraph::<u32, u32, petgraph::Directed, u32>::new();
+        let a = graph.add_node(1);
+        let b = graph.add_node(2);
+        let c = graph.add_node(3);
+        let ab = graph.add_edge(a, b, 2);
+        let ac = graph.add_edge(a, c, 5);
+        let bc = graph.add_edge(b, c, 1);
+        let mut dijkstra = Dijkstra::new(Some(c));
+        dijkstra.run(&graph, a);
+        let res = dijkstra.result();
+
+        let mut desired = VecDeque::<Box<dyn Step>>::new();
+        desired.push_back(Box::new(DistanceStep::new(a, 0.)));
+        desired.push_back(Box::new(NodeStep::new(a, NodeState::Queued)));
+        desired.push_back(Box::new(NodeStep::new(a, NodeState::Visited)));
+        // Newer edges come first.
+        desired.push_back(Box::new(EdgeStep::new(ac, EdgeState::Relaxed)));
+        desired.push_back(Box::new(DistanceStep::new(c, 5.)));
+        desired.push_back(Box::new(NodeStep::new(c, NodeState::Queued)));
+        desired.push_back(Box::new(EdgeStep::new(ab, EdgeState::Relaxed)));
+        desired.push_back(Box::new(DistanceStep::new(b, 2.)));
+        desired.push_back(Box::new(NodeStep::new(b, NodeState::Queued)));
+        desired.push_back(Box::new(NodeStep::new(b, NodeState::Visited)));
+        desired.push_back(Box::new(EdgeStep::new(bc, EdgeState::Relaxed)));
+        desired.push_back(Box::new(EdgeStep::new(ac, EdgeState::Rejected)));
+        desired.push_back(Box::new(DistanceStep::new(c, 3.)));
+        desired.push_back(Box::new(NodeStep::new(c, NodeState::Visited)));
+        desired.push_back(Box::new(EdgeStep::new(ab, EdgeState::OnPath)));
+        desired.push_back(Box::new(EdgeStep::new(bc, EdgeState::OnPath)));
+
+        assert_eq!(res.steps(), &desired);
+    }
+
+    #[test]
+    fn rejected_relaxation() {
+        let mut graph = StableGraph::<u32, u32, petgraph::Undirected, u32>::default();
+        let a = graph.add_node(1);
+        let b = graph.add_node(2);
+        let c = graph.add_node(3);
+        let ab = graph.add_edge(a, b, 1);
+        let ac = graph.add_edge(a, c, 1);
+        let bc = graph.add_edge(b, c, 1);
+        let mut dijkstra = Dijkstra::new(None);
+        dijkstra.run(&graph, a);
+        let res = dijkstra.result();
+
+        let mut desired = VecDeque::<Box<dyn Step>>::new();
+        desired.push_back(Box::new(DistanceStep::new(a, 0.)));
+        desired.push_back(Box::new(NodeStep::new(a, NodeState::Queued)));
+        desired.push_back(Box::new(NodeStep::new(a, NodeState::Visited)));
+        desired.push_back(Box::new(EdgeStep::new(ac, EdgeState::Relaxed)));
+        desired.push_back(Box::new(DistanceStep::new(c, 1.)));
+        desired.push_back(Box::new(NodeStep::new(c, NodeState::Queued)));
+        desired.push_back(Box::new(EdgeStep::new(ab, EdgeState::Relaxed)));
+        desired.push_back(Box::new(DistanceStep::new(b, 1.)));
+        desired.push_back(Box::new(NodeStep::new(b, NodeState::Queued)));
+        // Ties go to the smaller index, the edge back to a is skipped.
+        desired.push_back(Box::new(NodeStep::new(b, NodeState::Visited)));
+        desired.push_back(Box::new(EdgeStep::new(bc, EdgeState::Rejected)));
+        desired.push_back(Box::new(NodeStep::new(c, NodeState::Visited)));
+
+        assert_eq!(res.steps(), &desired);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::{HashMap, VecDeque};
+
+use dyn_partial_eq::DynPartialEq;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use serde::{Deserialize, Serialize};
+
+use super::step_algorithm::{step_json, Step};
+use super::StepAlgorithmResult;
+use crate::graph::Graph;
+
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ShowNode {
+    idx: NodeIndex,
+}
+
+impl Step for ShowNode {
+    fn apply_step(&self, graph: &mut Graph) {
+        if let Some(node) = graph.node_weight_mut(self.idx) {
+            node.set_hidden(false);
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("grow_node", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.idx]
+    }
+}
+
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ShowEdge {
+    idx: EdgeIndex,
+}
+
+impl Step for ShowEdge {
+    fn apply_step(&self, graph: &mut Graph) {
+        if let Some(edge) = graph.edge_weight_mut(self.idx) {
+            edge.set_hidden(false);
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("grow_edge", self)
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        vec![self.idx]
+    }
+}
+
+// Hides `nodes` and their edges, and returns steps that show them again: every node in order,
+// followed by its edges to the nodes already shown. Played like an algorithm, the layout settles
+// while the graph grows.
+pub fn grow(graph: &mut Graph, nodes: &[NodeIndex]) -> StepAlgorithmResult {
+    let order = nodes
+        .iter()
+        .enumerate()
+        .map(|(nr, idx)| (*idx, nr))
+        .collect::<HashMap<_, _>>();
+    let mut edges = vec![Vec::new(); nodes.len()];
+    for idx in graph.edge_indices().collect::<Vec<_>>() {
+        let Some((from, to)) = graph.edge_endpoints(idx) else {
+            continue;
+        };
+        // Edges to older nodes wait for the newer end.
+        if let Some(&last) = [from, to].iter().filter_map(|end| order.get(end)).max() {
+            graph[idx].set_hidden(true);
+            edges[last].push(idx);
+        }
+    }
+
+    let mut steps = VecDeque::<Box<dyn Step>>::new();
+    for (idx, edges) in nodes.iter().zip(edges) {
+        if let Some(node) = graph.node_weight_mut(*idx) {
+            node.set_hidden(true);
+            steps.push_back(Box::new(ShowNode { idx: *idx }));
+            steps.extend(
+                edges
+                    .into_iter()
+                    .map(|idx| Box::new(ShowEdge { idx }) as Box<dyn Step>),
+            );
+        }
+    }
+    StepAlgorithmResult::from_steps(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+
+    use super::grow;
+    use crate::graph::node::Node;
+    use crate::graph::{Graph, GraphOnCanvas, Position};
+
+    #[test]
+    fn nodes_appear_before_their_edges() {
+        let mut graph = Graph::new();
+        for nr in 0..3 {
+            graph.add_node(Node::new(Position::new(nr as f32 * 100., 0.)));
+        }
+        let node = NodeIndex::new;
+        graph.connect_nodes(node(2), node(0));
+        graph.connect_nodes(node(1), node(2));
+        graph.connect_nodes(node(0), node(1));
+
+        // Node 0 was there before, 2 and 1 are generated in that order.
+        let mut result = grow(&mut graph, &[node(2), node(1)]);
+        assert!(!graph[node(0)].is_hidden());
+        assert!(graph[node(1)].is_hidden() && graph[node(2)].is_hidden());
+        assert!(graph.edge_weights().all(|edge| edge.is_hidden()));
+        let names = result
+            .steps()
+            .iter()
+            .map(|step| step.to_json()["step"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "grow_node",
+                "grow_edge",
+                "grow_node",
+                "grow_edge",
+                "grow_edge"
+            ]
+        );
+        assert!(graph.node_from_point(Position::new(200., 0.)).is_none());
+
+        result.jump_to(&mut graph, 2);
+        assert!(!graph[node(2)].is_hidden());
+        assert!(graph.node_from_point(Position::new(200., 0.)).is_some());
+        assert!(graph[node(1)].is_hidden());
+        result.run_to_end(&mut graph);
+        assert!(graph.node_weights().all(|node| !node.is_hidden()));
+        assert!(graph.edge_weights().all(|edge| !edge.is_hidden()));
+
+        result.restart(&mut graph);
+        assert!(graph[node(2)].is_hidden());
+    }
+}
@@ -1,12 +1,29 @@
+mod astar;
+mod bellman_ford;
 mod bfs;
+mod biconnectivity;
 mod dfs;
+mod dijkstra;
+mod growth;
 mod scc;
+mod spanning_tree;
 mod step_algorithm;
 mod timer;
+mod topological_sort;
+mod trace;
 
+pub use astar::AStar;
+pub use bellman_ford::BellmanFord;
 pub use bfs::Bfs;
+pub use biconnectivity::Biconnectivity;
 pub use dfs::Dfs;
+pub use dijkstra::Dijkstra;
+pub use growth::grow;
 pub use scc::Scc;
-pub use step_algorithm::{DirectedStepAlgorithm, UndirectedStepAlgorithm};
-pub use step_algorithm::{StepAlgorithm, StepAlgorithmResult};
+pub use spanning_tree::{Kruskal, Prim};
+pub use step_algorithm::has_negative_weights;
+pub use step_algorithm::{AlgorithmOutcome, StaleStep, Step, StepAlgorithm, StepAlgorithmResult};
+pub use step_algorithm::{DirectedStepAlgorithm, EdgeWeight, UndirectedStepAlgorithm};
 pub use timer::Timer;
+pub use topological_sort::{SortMethod, TopologicalSort};
+pub use trace::step_from_json;
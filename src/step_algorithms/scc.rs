@@ -1,17 +1,20 @@
 use std::collections::{HashMap, VecDeque};
 
+use crate::graph::Color;
 use dyn_partial_eq::DynPartialEq;
-use petgraph::{graph::NodeIndex, Graph};
-use tetra::graphics::Color;
+use petgraph::{graph::NodeIndex, stable_graph::StableGraph};
+use serde::{Deserialize, Serialize};
 
 use crate::graph::GraphOnCanvas;
 
 use super::{
-    dfs, step_algorithm::Step, Dfs, DirectedStepAlgorithm, StepAlgorithm, StepAlgorithmResult,
+    dfs,
+    step_algorithm::{step_json, Step},
+    AlgorithmOutcome, Dfs, DirectedStepAlgorithm, EdgeWeight, StepAlgorithm, StepAlgorithmResult,
 };
 
 // Found them as rgb8 but rgb8 function isn't const
-static COLORS: [Color; 10] = [
+pub(super) static COLORS: [Color; 10] = [
     Color::rgb(1., 95. / 255., 93. / 255.),
     Color::rgb(0., 204. / 255., 191. / 255.),
     Color::rgb(114. / 255., 242. / 255., 235. / 255.),
@@ -24,21 +27,35 @@ static COLORS: [Color; 10] = [
     Color::rgb(54. / 255., 52. / 255., 50. / 255.),
 ];
 
-#[derive(DynPartialEq, PartialEq, Debug)]
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Reverse {}
 
 impl Step for Reverse {
     fn apply_step(&self, graph: &mut crate::graph::Graph) {
         graph.edge_weights_mut().for_each(|edge| edge.reverse());
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("scc_reverse", self)
+    }
+
+    fn changes_whole_graph(&self) -> bool {
+        true
+    }
 }
 
-#[derive(DynPartialEq, PartialEq, Debug)]
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct PaintComponent {
     color: Color,
     indices: Vec<NodeIndex>,
 }
 
+impl PaintComponent {
+    pub fn new(color: Color, indices: Vec<NodeIndex>) -> PaintComponent {
+        PaintComponent { color, indices }
+    }
+}
+
 impl Step for PaintComponent {
     fn apply_step(&self, graph: &mut crate::graph::Graph) {
         self.indices.iter().for_each(|idx| {
@@ -47,9 +64,17 @@ impl Step for PaintComponent {
             }
         });
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("scc_paint_component", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        self.indices.clone()
+    }
 }
 
-#[derive(DynPartialEq, PartialEq, Debug)]
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct ResetState {}
 
 impl Step for ResetState {
@@ -57,24 +82,41 @@ impl Step for ResetState {
         graph.reset_state();
         graph.edge_weights_mut().for_each(|edge| edge.disable());
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("scc_reset_state", self)
+    }
+
+    fn changes_whole_graph(&self) -> bool {
+        true
+    }
 }
 
-#[derive(DynPartialEq, PartialEq, Debug)]
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct EnableEdges {}
 
 impl Step for EnableEdges {
     fn apply_step(&self, graph: &mut crate::graph::Graph) {
         graph.edge_weights_mut().for_each(|edge| edge.enable());
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("scc_enable_edges", self)
+    }
+
+    fn changes_whole_graph(&self) -> bool {
+        true
+    }
 }
 
+#[derive(Default)]
 pub struct Scc {
     steps: VecDeque<Box<dyn Step>>,
     components: HashMap<usize, Vec<NodeIndex>>,
 }
 
 impl DirectedStepAlgorithm for Scc {
-    fn run<N, E>(&mut self, graph: &Graph<N, E>, _start_idx: NodeIndex) {
+    fn run<N, E: EdgeWeight>(&mut self, graph: &StableGraph<N, E>, _start_idx: NodeIndex) {
         let dfs = self.postorder_dfs(graph);
         let rev_dfs = self.reversed_dfs(graph, dfs.postorder());
         self.steps = dfs.into_steps();
@@ -88,20 +130,21 @@ impl DirectedStepAlgorithm for Scc {
         self.steps.push_back(Box::new(EnableEdges {}));
     }
 
-    fn result(self) -> super::StepAlgorithmResult {
+    fn result(mut self) -> super::StepAlgorithmResult {
+        let components = (0..self.components.len())
+            .filter_map(|nr| self.components.remove(&nr))
+            .collect();
         StepAlgorithmResult::from_steps(self.steps)
+            .with_outcome(AlgorithmOutcome::Components { components })
     }
 }
 
 impl Scc {
     pub fn new() -> Scc {
-        Scc {
-            steps: VecDeque::new(),
-            components: HashMap::new(),
-        }
+        Scc::default()
     }
 
-    fn postorder_dfs<N, E>(&mut self, graph: &Graph<N, E>) -> Dfs {
+    fn postorder_dfs<N, E: EdgeWeight>(&mut self, graph: &StableGraph<N, E>) -> Dfs {
         let mut dfs = Dfs::from_graph(graph);
         graph.node_indices().for_each(|idx| {
             if let Some(state) = dfs.states().get(&idx) {
@@ -113,7 +156,7 @@ impl Scc {
         dfs
     }
 
-    fn reversed_dfs<N, E>(&mut self, graph: &Graph<N, E>, order: &[NodeIndex]) -> Dfs {
+    fn reversed_dfs<N, E>(&mut self, graph: &StableGraph<N, E>, order: &[NodeIndex]) -> Dfs {
         let mut dfs = Dfs::from_graph(graph);
         let mut nr = 0;
         order.iter().rev().for_each(|idx| {
@@ -121,10 +164,7 @@ impl Scc {
                 if matches!(state, dfs::NodeState::NotVisited) {
                     dfs.dfs_reversed(graph, *idx);
                     self.components.insert(nr, dfs.postorder().clone());
-                    let paint_step = PaintComponent {
-                        color: COLORS[nr % 10],
-                        indices: dfs.postorder().clone(),
-                    };
+                    let paint_step = PaintComponent::new(COLORS[nr % 10], dfs.postorder().clone());
                     dfs.postorder_mut().clear();
                     dfs.steps_mut().push_back(Box::new(paint_step));
                     nr += 1;
@@ -139,13 +179,15 @@ impl Scc {
 mod tests {
     use std::collections::HashSet;
 
+    use petgraph::stable_graph::StableGraph;
+
     use crate::step_algorithms::DirectedStepAlgorithm;
 
     use super::Scc;
 
     #[test]
     fn two_triangles() {
-        let mut graph = petgraph::Graph::<u32, u32, petgraph::Directed>::new();
+        let mut graph = StableGraph::<u32, u32, petgraph::Directed>::new();
         let a = graph.add_node(0);
         let b = graph.add_node(0);
         let c = graph.add_node(0);
@@ -0,0 +1,340 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use crate::graph::Color;
+use dyn_partial_eq::DynPartialEq;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use petgraph::Undirected;
+use serde::{Deserialize, Serialize};
+
+use super::bfs::{NodeState, NodeStep};
+use super::scc::{PaintComponent, COLORS};
+use super::step_algorithm::{step_json, EdgeWeight, Step};
+use super::{AlgorithmOutcome, StepAlgorithmResult, UndirectedStepAlgorithm};
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeState {
+    Considered,
+    Accepted,
+    Rejected,
+    // Leaves the tree, waiting in Prim's queue.
+    Frontier,
+}
+
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EdgeStep {
+    idx: EdgeIndex,
+    to_state: EdgeState,
+}
+
+impl EdgeStep {
+    pub fn new(idx: EdgeIndex, to_state: EdgeState) -> EdgeStep {
+        EdgeStep { idx, to_state }
+    }
+}
+
+impl Step for EdgeStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        if let Some(edge) = graph.edge_weight_mut(self.idx) {
+            edge.enable();
+            edge.set_color(match self.to_state {
+                EdgeState::Considered => Color::BLUE,
+                EdgeState::Accepted => Color::GREEN,
+                EdgeState::Rejected => Color::RED,
+                EdgeState::Frontier => Color::rgb(1., 0.6, 0.),
+            });
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("mst_edge", self)
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        vec![self.idx]
+    }
+}
+
+fn outcome(edges: Vec<EdgeIndex>, weight: f32) -> AlgorithmOutcome {
+    AlgorithmOutcome::SpanningTree { edges, weight }
+}
+
+// Minimum spanning forest, the start node is ignored. Nodes are painted by their current
+// component, nodes that are still alone stay as they are.
+pub struct Kruskal {
+    steps: VecDeque<Box<dyn Step>>,
+    // Representative of every node, and the members of every representative.
+    component: HashMap<NodeIndex, NodeIndex>,
+    members: HashMap<NodeIndex, Vec<NodeIndex>>,
+    colors: HashMap<NodeIndex, Color>,
+    tree: Vec<EdgeIndex>,
+    weight: f32,
+}
+
+impl UndirectedStepAlgorithm for Kruskal {
+    fn run<N, E: EdgeWeight>(
+        &mut self,
+        graph: &StableGraph<N, E, Undirected>,
+        _start_idx: NodeIndex,
+    ) {
+        for idx in graph.node_indices() {
+            self.component.insert(idx, idx);
+            self.members.insert(idx, vec![idx]);
+        }
+
+        // Ties go to the smaller index so the steps don't depend on the sort.
+        let mut edges = graph.edge_references().collect::<Vec<_>>();
+        edges.sort_by(|a, b| {
+            a.weight()
+                .weight()
+                .total_cmp(&b.weight().weight())
+                .then_with(|| a.id().cmp(&b.id()))
+        });
+
+        for edge in edges {
+            self.steps
+                .push_back(Box::new(EdgeStep::new(edge.id(), EdgeState::Considered)));
+            if self.union(edge.source(), edge.target()) {
+                self.steps
+                    .push_back(Box::new(EdgeStep::new(edge.id(), EdgeState::Accepted)));
+                self.tree.push(edge.id());
+                self.weight += edge.weight().weight();
+            } else {
+                self.steps
+                    .push_back(Box::new(EdgeStep::new(edge.id(), EdgeState::Rejected)));
+            }
+        }
+    }
+
+    fn result(self) -> StepAlgorithmResult {
+        StepAlgorithmResult::from_steps(self.steps).with_outcome(outcome(self.tree, self.weight))
+    }
+}
+
+impl Kruskal {
+    pub fn new() -> Kruskal {
+        Kruskal {
+            steps: VecDeque::new(),
+            component: HashMap::new(),
+            members: HashMap::new(),
+            colors: HashMap::new(),
+            tree: Vec::new(),
+            weight: 0.,
+        }
+    }
+
+    // Moves the smaller component into the larger one, false if they already were one.
+    fn union(&mut self, a: NodeIndex, b: NodeIndex) -> bool {
+        let (mut large, mut small) = (self.component[&a], self.component[&b]);
+        if large == small {
+            return false;
+        }
+        if self.members[&large].len() < self.members[&small].len() {
+            std::mem::swap(&mut large, &mut small);
+        }
+
+        let moved = self.members.remove(&small).unwrap_or_default();
+        moved.iter().for_each(|idx| {
+            self.component.insert(*idx, large);
+        });
+        self.colors.remove(&small);
+
+        // Two lone nodes start a new component.
+        let paint = match self.colors.get(&large) {
+            Some(color) => PaintComponent::new(*color, moved.clone()),
+            None => {
+                // A color no other component has, as long as there are enough.
+                let color = COLORS
+                    .iter()
+                    .find(|color| !self.colors.values().any(|used| used == *color))
+                    .copied()
+                    .unwrap_or(COLORS[self.colors.len() % COLORS.len()]);
+                self.colors.insert(large, color);
+                let mut indices = self.members[&large].clone();
+                indices.extend(&moved);
+                PaintComponent::new(color, indices)
+            }
+        };
+        self.steps.push_back(Box::new(paint));
+        if let Some(members) = self.members.get_mut(&large) {
+            members.extend(moved);
+        }
+        true
+    }
+}
+
+impl Default for Kruskal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Min-heap entry for Prim's frontier, ties go to the smaller edge index.
+#[derive(PartialEq)]
+struct FrontierEdge {
+    weight: f32,
+    idx: EdgeIndex,
+    to: NodeIndex,
+}
+
+impl Eq for FrontierEdge {}
+
+impl Ord for FrontierEdge {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .weight
+            .total_cmp(&self.weight)
+            .then_with(|| other.idx.cmp(&self.idx))
+    }
+}
+
+impl PartialOrd for FrontierEdge {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Spanning tree of the start node's component. Edges leaving the tree stay in the queue until
+// they are taken, so some of them are rejected later on.
+pub struct Prim {
+    steps: VecDeque<Box<dyn Step>>,
+    tree: Vec<EdgeIndex>,
+    weight: f32,
+}
+
+impl UndirectedStepAlgorithm for Prim {
+    fn run<N, E: EdgeWeight>(
+        &mut self,
+        graph: &StableGraph<N, E, Undirected>,
+        start_idx: NodeIndex,
+    ) {
+        let mut queue = BinaryHeap::new();
+        let mut in_tree = HashSet::new();
+
+        self.add_to_tree(graph, start_idx, &mut in_tree, &mut queue);
+        while let Some(FrontierEdge { weight, idx, to }) = queue.pop() {
+            if in_tree.contains(&to) {
+                self.steps
+                    .push_back(Box::new(EdgeStep::new(idx, EdgeState::Rejected)));
+                continue;
+            }
+            self.steps
+                .push_back(Box::new(EdgeStep::new(idx, EdgeState::Accepted)));
+            self.tree.push(idx);
+            self.weight += weight;
+            self.add_to_tree(graph, to, &mut in_tree, &mut queue);
+        }
+    }
+
+    fn result(self) -> StepAlgorithmResult {
+        StepAlgorithmResult::from_steps(self.steps).with_outcome(outcome(self.tree, self.weight))
+    }
+}
+
+impl Prim {
+    pub fn new() -> Prim {
+        Prim {
+            steps: VecDeque::new(),
+            tree: Vec::new(),
+            weight: 0.,
+        }
+    }
+
+    fn add_to_tree<N, E: EdgeWeight>(
+        &mut self,
+        graph: &StableGraph<N, E, Undirected>,
+        idx: NodeIndex,
+        in_tree: &mut HashSet<NodeIndex>,
+        queue: &mut BinaryHeap<FrontierEdge>,
+    ) {
+        in_tree.insert(idx);
+        self.steps
+            .push_back(Box::new(NodeStep::new(idx, NodeState::Visited)));
+
+        let mut walker = graph.neighbors(idx).detach();
+        while let Some((edge_idx, other_idx)) = walker.next(graph) {
+            if in_tree.contains(&other_idx) {
+                continue;
+            }
+            self.steps
+                .push_back(Box::new(EdgeStep::new(edge_idx, EdgeState::Frontier)));
+            self.steps
+                .push_back(Box::new(NodeStep::new(other_idx, NodeState::Queued)));
+            queue.push(FrontierEdge {
+                weight: graph[edge_idx].weight(),
+                idx: edge_idx,
+                to: other_idx,
+            });
+        }
+    }
+}
+
+impl Default for Prim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::{Kruskal, Prim};
+    use crate::step_algorithms::{AlgorithmOutcome, UndirectedStepAlgorithm};
+
+    // A square with one diagonal, the heavy side and the diagonal aren't in the tree.
+    fn square() -> StableGraph<u32, u32, petgraph::Undirected> {
+        let mut graph = StableGraph::default();
+        let nodes = (0..4).map(|nr| graph.add_node(nr)).collect::<Vec<_>>();
+        graph.add_edge(nodes[0], nodes[1], 1);
+        graph.add_edge(nodes[1], nodes[2], 2);
+        graph.add_edge(nodes[2], nodes[3], 1);
+        graph.add_edge(nodes[3], nodes[0], 5);
+        graph.add_edge(nodes[0], nodes[2], 3);
+        graph
+    }
+
+    fn tree_of(outcome: &AlgorithmOutcome) -> (Vec<usize>, f32) {
+        match outcome {
+            AlgorithmOutcome::SpanningTree { edges, weight } => {
+                let mut edges = edges.iter().map(|idx| idx.index()).collect::<Vec<_>>();
+                edges.sort();
+                (edges, *weight)
+            }
+            _ => panic!("not a spanning tree"),
+        }
+    }
+
+    #[test]
+    fn kruskal_and_prim_agree() {
+        let graph = square();
+        let mut kruskal = Kruskal::new();
+        kruskal.run(&graph, 0.into());
+        let mut prim = Prim::new();
+        prim.run(&graph, 3.into());
+
+        assert_eq!(tree_of(kruskal.result().outcome()), (vec![0, 1, 2], 4.));
+        assert_eq!(tree_of(prim.result().outcome()), (vec![0, 1, 2], 4.));
+    }
+
+    #[test]
+    fn kruskal_spans_every_component() {
+        let mut graph = square();
+        let a = graph.add_node(4);
+        let b = graph.add_node(5);
+        graph.add_edge(a, b, 7);
+        graph.add_edge(a, a, 0);
+
+        let mut kruskal = Kruskal::new();
+        kruskal.run(&graph, 0.into());
+        assert_eq!(tree_of(kruskal.result().outcome()), (vec![0, 1, 2, 5], 11.));
+
+        // Prim stays in the start node's component.
+        let mut prim = Prim::new();
+        prim.run(&graph, a);
+        assert_eq!(tree_of(prim.result().outcome()), (vec![5], 7.));
+    }
+}
@@ -1,72 +1,513 @@
-use std::{any::Any, collections::VecDeque, fmt::Debug};
+use std::collections::{HashSet, VecDeque};
+use std::{any::Any, fmt, fmt::Debug};
 
 use dyn_partial_eq::dyn_partial_eq;
-use petgraph::graph::NodeIndex;
-use petgraph::{Directed, EdgeType, Graph, Undirected};
-
-use tetra::Context;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::{Directed, EdgeType, Undirected};
+use serde::Serialize;
 
 use super::Timer;
+use crate::constants::BASE_PLAYBACK_SPEED;
+use crate::graph::edge::EdgeVisuals;
+use crate::graph::node::NodeVisuals;
 
 #[dyn_partial_eq]
 pub trait Step: Any + Debug {
     fn apply_step(&self, graph: &mut crate::graph::Graph);
+
+    // One object per step in traces, see `step_json`.
+    fn to_json(&self) -> serde_json::Value;
+
+    // Nodes and edges the step changes, replays check that they still exist.
+    fn nodes(&self) -> Vec<NodeIndex> {
+        Vec::new()
+    }
+
+    fn edges(&self) -> Vec<EdgeIndex> {
+        Vec::new()
+    }
+
+    // For steps that change nodes or edges they don't list, like reversing every edge. Undoing
+    // them saves the whole graph, other steps only save their own nodes and edges.
+    fn changes_whole_graph(&self) -> bool {
+        false
+    }
+}
+
+// The step's fields with its name under "step". Names are unique across algorithms.
+pub fn step_json<S: Serialize>(name: &str, step: &S) -> serde_json::Value {
+    let mut value = serde_json::to_value(step).unwrap_or_default();
+    if let serde_json::Value::Object(fields) = &mut value {
+        fields.insert(String::from("step"), serde_json::Value::from(name));
+    }
+    value
+}
+
+// What the algorithm found, for callers that only want the answer and not the steps.
+#[derive(Serialize, Debug, PartialEq, Clone, Default)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum AlgorithmOutcome {
+    #[default]
+    None,
+    // Nodes in the order they were reached.
+    Order {
+        order: Vec<NodeIndex>,
+    },
+    Components {
+        components: Vec<Vec<NodeIndex>>,
+    },
+    // Distances of reachable nodes by index, and the path to the target if one was given and reached.
+    Distances {
+        distances: Vec<(NodeIndex, f32)>,
+        path: Vec<NodeIndex>,
+    },
+    // Nodes a search to one target expanded in order, the path to it and its cost if it was reached.
+    Search {
+        expanded: Vec<NodeIndex>,
+        path: Vec<NodeIndex>,
+        distance: Option<f32>,
+    },
+    // Nodes of a cycle with negative total weight, in the order the edges go.
+    NegativeCycle {
+        cycle: Vec<NodeIndex>,
+    },
+    // Nodes of a cycle that keeps the graph from being sorted, in the order the edges go.
+    Cycle {
+        cycle: Vec<NodeIndex>,
+    },
+    // Nodes and edges whose removal disconnects the graph, by index.
+    Biconnectivity {
+        articulation_points: Vec<NodeIndex>,
+        bridges: Vec<EdgeIndex>,
+    },
+    // Edges of a minimum spanning tree, or forest, and their total weight.
+    SpanningTree {
+        edges: Vec<EdgeIndex>,
+        weight: f32,
+    },
+}
+
+// Lets algorithms read edge weights, whatever the edge type of the graph is.
+pub trait EdgeWeight {
+    fn weight(&self) -> f32;
+}
+
+impl EdgeWeight for f32 {
+    fn weight(&self) -> f32 {
+        *self
+    }
+}
+
+impl EdgeWeight for u32 {
+    fn weight(&self) -> f32 {
+        *self as f32
+    }
+}
+
+impl EdgeWeight for i32 {
+    fn weight(&self) -> f32 {
+        *self as f32
+    }
+}
+
+// Dijkstra and A* give wrong distances on these, Bellman-Ford doesn't.
+pub fn has_negative_weights<N, E: EdgeWeight, D: EdgeType>(graph: &StableGraph<N, E, D>) -> bool {
+    graph.edge_weights().any(|edge| edge.weight() < 0.)
 }
 
 pub trait StepAlgorithm {
-    fn run<N, E, D: EdgeType>(&mut self, graph: &Graph<N, E, D>, start_idx: NodeIndex);
+    fn run<N, E: EdgeWeight, D: EdgeType>(
+        &mut self,
+        graph: &StableGraph<N, E, D>,
+        start_idx: NodeIndex,
+    );
     fn result(self) -> StepAlgorithmResult;
 }
 
 pub trait UndirectedStepAlgorithm {
-    fn run<N, E>(&mut self, graph: &Graph<N, E, Undirected>, start_idx: NodeIndex);
+    fn run<N, E: EdgeWeight>(
+        &mut self,
+        graph: &StableGraph<N, E, Undirected>,
+        start_idx: NodeIndex,
+    );
     fn result(self) -> StepAlgorithmResult;
 }
 
 pub trait DirectedStepAlgorithm {
-    fn run<N, E>(&mut self, graph: &Graph<N, E, Directed>, start_idx: NodeIndex);
+    fn run<N, E: EdgeWeight>(&mut self, graph: &StableGraph<N, E, Directed>, start_idx: NodeIndex);
     fn result(self) -> StepAlgorithmResult;
 }
 
+// How the graph looked before a step, only for the nodes and edges that the step changed.
+struct StepUndo {
+    nodes: Vec<(NodeIndex, NodeVisuals)>,
+    edges: Vec<(EdgeIndex, EdgeVisuals)>,
+}
+
+impl StepUndo {
+    fn restore(&self, graph: &mut crate::graph::Graph) {
+        // Nodes and edges removed in the meantime are skipped.
+        for (idx, visuals) in &self.nodes {
+            if let Some(node) = graph.node_weight_mut(*idx) {
+                node.set_visuals(visuals.clone());
+            }
+        }
+        for (idx, visuals) in &self.edges {
+            if let Some(edge) = graph.edge_weight_mut(*idx) {
+                edge.set_visuals(visuals.clone());
+            }
+        }
+    }
+}
+
+// Steps don't add or remove anything, so both lists hold the same indices in the same order.
+fn changed<I: PartialEq, T: PartialEq>(before: Vec<(I, T)>, after: Vec<(I, T)>) -> Vec<(I, T)> {
+    before
+        .into_iter()
+        .zip(after)
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|(old, _)| old)
+        .collect()
+}
+
+fn node_visuals(
+    graph: &crate::graph::Graph,
+    indices: &[NodeIndex],
+) -> Vec<(NodeIndex, NodeVisuals)> {
+    indices
+        .iter()
+        .filter_map(|idx| graph.node_weight(*idx).map(|node| (*idx, node.visuals())))
+        .collect()
+}
+
+fn edge_visuals(
+    graph: &crate::graph::Graph,
+    indices: &[EdgeIndex],
+) -> Vec<(EdgeIndex, EdgeVisuals)> {
+    indices
+        .iter()
+        .filter_map(|idx| graph.edge_weight(*idx).map(|edge| (*idx, edge.visuals())))
+        .collect()
+}
+
+// A step whose nodes or edges are gone from the graph, with the missing ones.
+#[derive(Debug, PartialEq)]
+pub struct StaleStep {
+    step: usize,
+    nodes: Vec<NodeIndex>,
+    edges: Vec<EdgeIndex>,
+}
+
+impl StaleStep {
+    // Zero based.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn nodes(&self) -> &Vec<NodeIndex> {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &Vec<EdgeIndex> {
+        &self.edges
+    }
+}
+
+impl fmt::Display for StaleStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let missing = self
+            .nodes
+            .iter()
+            .map(|idx| format!("node {}", idx.index()))
+            .chain(self.edges.iter().map(|idx| format!("edge {}", idx.index())))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "step {}: no {}", self.step + 1, missing)
+    }
+}
+
+// Steps are kept after being shown, `current` of them have been applied to the graph.
 pub struct StepAlgorithmResult {
     steps: VecDeque<Box<dyn Step>>,
+    current: usize,
+    // One for every applied step.
+    undo: Vec<StepUndo>,
     timer: Timer,
+    outcome: AlgorithmOutcome,
 }
 
 impl StepAlgorithmResult {
     pub fn from_steps(steps: VecDeque<Box<dyn Step>>) -> StepAlgorithmResult {
-        let timer = Timer::new(0.3, true);
-        StepAlgorithmResult { steps, timer }
+        let timer = Timer::new(1. / BASE_PLAYBACK_SPEED, true);
+        StepAlgorithmResult {
+            steps,
+            current: 0,
+            undo: Vec::new(),
+            timer,
+            outcome: AlgorithmOutcome::None,
+        }
+    }
+
+    pub fn with_outcome(mut self, outcome: AlgorithmOutcome) -> StepAlgorithmResult {
+        self.outcome = outcome;
+        self
+    }
+
+    pub fn outcome(&self) -> &AlgorithmOutcome {
+        &self.outcome
     }
 
     pub fn steps(&self) -> &VecDeque<Box<dyn Step>> {
         &self.steps
     }
 
+    // Steps referring to nodes or edges the graph doesn't have, e.g. when replaying a saved run
+    // on a graph that was edited since.
+    pub fn stale_steps(&self, graph: &crate::graph::Graph) -> Vec<StaleStep> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(step, alg_step)| StaleStep {
+                step,
+                nodes: alg_step
+                    .nodes()
+                    .into_iter()
+                    .filter(|idx| !graph.contains_node(*idx))
+                    .collect(),
+                edges: alg_step
+                    .edges()
+                    .into_iter()
+                    .filter(|idx| graph.edge_weight(*idx).is_none())
+                    .collect(),
+            })
+            .filter(|stale| !stale.nodes.is_empty() || !stale.edges.is_empty())
+            .collect()
+    }
+
+    // Every node some step refers to, whether the graph still has it or not.
+    pub fn referenced_nodes(&self) -> HashSet<NodeIndex> {
+        self.steps.iter().flat_map(|step| step.nodes()).collect()
+    }
+
+    // Number of steps already shown.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current == self.steps.len()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.timer.is_active()
+    }
+
     fn timer_mut(&mut self) -> &mut Timer {
         &mut self.timer
     }
 
-    fn start_timer(&mut self) {
-        self.timer_mut().start();
+    pub fn play(&mut self) {
+        if !self.is_finished() {
+            self.timer_mut().start();
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.timer_mut().stop();
+    }
+
+    // In steps per second.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.timer_mut().set_time(1. / speed);
     }
 
-    pub fn update(&mut self, ctx: &mut Context, graph: &mut crate::graph::Graph) {
-        if self.timer_mut().update(ctx) {
-            if let Some(alg_step) = self.steps.pop_front() {
-                alg_step.apply_step(graph);
+    pub fn step_forward(&mut self, graph: &mut crate::graph::Graph) {
+        if let Some(alg_step) = self.steps.get(self.current) {
+            let (node_indices, edge_indices) = if alg_step.changes_whole_graph() {
+                (
+                    graph.node_indices().collect(),
+                    graph.edge_indices().collect(),
+                )
             } else {
-                self.timer_mut().stop();
-            }
+                (alg_step.nodes(), alg_step.edges())
+            };
+            let nodes = node_visuals(graph, &node_indices);
+            let edges = edge_visuals(graph, &edge_indices);
+            alg_step.apply_step(graph);
+            self.undo.push(StepUndo {
+                nodes: changed(nodes, node_visuals(graph, &node_indices)),
+                edges: changed(edges, edge_visuals(graph, &edge_indices)),
+            });
+            self.current += 1;
+        }
+        if self.is_finished() {
+            self.pause();
         }
     }
 
-    pub fn show_algorithm(&mut self, graph: &mut crate::graph::Graph) {
+    pub fn step_back(&mut self, graph: &mut crate::graph::Graph) {
+        if let Some(undo) = self.undo.pop() {
+            undo.restore(graph);
+            self.current -= 1;
+        }
+    }
+
+    // The graph as it was before the first step, the playback stays where it is.
+    pub fn rewound(&self, graph: &crate::graph::Graph) -> crate::graph::Graph {
+        let mut graph = graph.clone();
+        for undo in self.undo.iter().rev() {
+            undo.restore(&mut graph);
+        }
+        graph
+    }
+
+    // Shows the graph as it was after `step` steps.
+    pub fn jump_to(&mut self, graph: &mut crate::graph::Graph, step: usize) {
+        while self.current > step {
+            self.step_back(graph);
+        }
+        while self.current < step.min(self.steps.len()) {
+            self.step_forward(graph);
+        }
+    }
+
+    pub fn run_to_end(&mut self, graph: &mut crate::graph::Graph) {
+        while !self.is_finished() {
+            self.step_forward(graph);
+        }
+    }
+
+    // Shows the algorithm from the beginning, paused on the first step.
+    pub fn restart(&mut self, graph: &mut crate::graph::Graph) {
+        self.jump_to(graph, 0);
+        self.pause();
+    }
+
+    pub fn update(&mut self, delta: f32, graph: &mut crate::graph::Graph) {
+        if self.timer_mut().update(delta) {
+            self.step_forward(graph);
+        }
+    }
+
+    fn prepare_graph(&self, graph: &mut crate::graph::Graph) {
         // Allow node to move while the algorithm is being showcased
         for edge in graph.edge_weights_mut() {
             edge.disable();
+            edge.set_hidden(false);
+        }
+        // Annotations of the previous algorithm would be misleading. A graph that was still
+        // growing is shown whole.
+        for node in graph.node_weights_mut() {
+            node.set_annotation(None);
+            node.set_hidden(false);
+        }
+    }
+
+    pub fn show_algorithm(&mut self, graph: &mut crate::graph::Graph) {
+        self.prepare_graph(graph);
+        self.play();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::{changed, Step, StepAlgorithmResult};
+    use crate::graph::node::Node;
+    use crate::graph::GraphOnCanvas;
+    use crate::graph::{Color, Graph, Position};
+    use crate::step_algorithms::bfs::{NodeState, NodeStep};
+    use crate::step_algorithms::scc::Reverse;
+    use petgraph::graph::NodeIndex;
+
+    #[test]
+    fn playback_moves_through_steps() {
+        let mut graph = Graph::new();
+        let mut steps = VecDeque::<Box<dyn Step>>::new();
+        for nr in 0..3 {
+            steps.push_back(Box::new(NodeStep::new(
+                NodeIndex::new(nr),
+                NodeState::Visited,
+            )));
         }
+        let mut result = StepAlgorithmResult::from_steps(steps);
+        result.show_algorithm(&mut graph);
+        assert!(result.is_playing());
+
+        result.pause();
+        result.step_forward(&mut graph);
+        assert_eq!(result.current(), 1);
+        assert!(!result.is_playing());
+
+        result.run_to_end(&mut graph);
+        assert_eq!(result.current(), 3);
+        assert!(result.is_finished());
+        // Nothing left to play.
+        result.play();
+        assert!(!result.is_playing());
+        assert_eq!(result.steps().len(), 3);
+
+        result.step_back(&mut graph);
+        assert_eq!(result.current(), 2);
+        result.jump_to(&mut graph, 10);
+        assert_eq!(result.current(), 3);
+
+        result.restart(&mut graph);
+        assert_eq!(result.current(), 0);
+        assert!(!result.is_finished());
+        result.step_back(&mut graph);
+        assert_eq!(result.current(), 0);
+    }
+
+    #[test]
+    fn only_changes_are_kept() {
+        let before = vec![(0, "white"), (1, "white"), (2, "green")];
+        let after = vec![(0, "white"), (1, "green"), (2, "white")];
+        assert_eq!(changed(before, after), vec![(1, "white"), (2, "green")]);
+    }
+
+    #[test]
+    fn undo_saves_what_the_step_changes() {
+        let mut graph = Graph::new();
+        let nodes = (0..3)
+            .map(|_| graph.add_node(Node::new(Position::zero())))
+            .collect::<Vec<_>>();
+        graph.connect_nodes(nodes[0], nodes[1]);
+        graph.connect_nodes(nodes[1], nodes[2]);
+        let mut steps = VecDeque::<Box<dyn Step>>::new();
+        steps.push_back(Box::new(NodeStep::new(nodes[1], NodeState::Visited)));
+        steps.push_back(Box::new(Reverse {}));
+        let mut result = StepAlgorithmResult::from_steps(steps);
+
+        result.run_to_end(&mut graph);
+        assert_eq!(result.undo[0].nodes.len(), 1);
+        assert!(result.undo[0].edges.is_empty());
+        // Reversing doesn't list its edges, they are saved anyway.
+        assert_eq!(result.undo[1].edges.len(), 2);
+        assert!(graph.edge_weights().all(|edge| edge.is_reversed()));
+        result.step_back(&mut graph);
+        assert!(graph.edge_weights().all(|edge| !edge.is_reversed()));
+    }
+
+    #[test]
+    fn step_back_restores_colors() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(Node::new(Position::zero()));
+        let mut steps = VecDeque::<Box<dyn Step>>::new();
+        steps.push_back(Box::new(NodeStep::new(a, NodeState::Queued)));
+        steps.push_back(Box::new(NodeStep::new(a, NodeState::Visited)));
+        let mut result = StepAlgorithmResult::from_steps(steps);
 
-        self.start_timer();
+        result.run_to_end(&mut graph);
+        let visited = graph[a].color();
+        assert_ne!(visited, Color::WHITE);
+        result.step_back(&mut graph);
+        assert_ne!(graph[a].color(), visited);
+        assert_eq!(result.rewound(&graph)[a].color(), Color::WHITE);
+        assert_ne!(graph[a].color(), Color::WHITE);
+        result.step_back(&mut graph);
+        assert_eq!(graph[a].color(), Color::WHITE);
     }
 }
@@ -1,5 +1,3 @@
-use tetra::Context;
-
 pub struct Timer {
     time_remaining: f32,
     active: bool,
@@ -22,6 +20,15 @@ impl Timer {
         self.time_remaining = self.time;
     }
 
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    // Takes effect from the next period.
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
     pub fn stop(&mut self) {
         self.active = false;
         self.time_remaining = 0.;
@@ -40,9 +47,10 @@ impl Timer {
         }
     }
 
-    pub fn update(&mut self, ctx: &mut Context) -> bool {
+    // `delta` is the time that passed since the last update, in seconds.
+    pub fn update(&mut self, delta: f32) -> bool {
         if self.active {
-            self.time_remaining -= tetra::time::get_delta_time(ctx).as_secs_f32();
+            self.time_remaining -= delta;
             self.finished()
         } else {
             false
@@ -0,0 +1,316 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use dyn_partial_eq::DynPartialEq;
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::{Directed, Direction};
+use serde::{Deserialize, Serialize};
+
+use super::bellman_ford::CycleStep;
+use super::bfs::{NodeState, NodeStep};
+use super::dfs;
+use super::step_algorithm::{step_json, EdgeWeight, Step};
+use super::{AlgorithmOutcome, Dfs, DirectedStepAlgorithm, StepAlgorithm, StepAlgorithmResult};
+
+// Edges into the node from nodes that aren't sorted yet.
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct InDegreeStep {
+    idx: NodeIndex,
+    in_degree: usize,
+}
+
+impl InDegreeStep {
+    pub fn new(idx: NodeIndex, in_degree: usize) -> InDegreeStep {
+        InDegreeStep { idx, in_degree }
+    }
+}
+
+impl Step for InDegreeStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        if let Some(node) = graph.node_weight_mut(self.idx) {
+            node.set_annotation(Some(format!("in {}", self.in_degree)));
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("topo_in_degree", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.idx]
+    }
+}
+
+// The node's place in the sorted order, counted from 1.
+#[derive(DynPartialEq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PlaceStep {
+    idx: NodeIndex,
+    place: usize,
+}
+
+impl PlaceStep {
+    pub fn new(idx: NodeIndex, place: usize) -> PlaceStep {
+        PlaceStep { idx, place }
+    }
+}
+
+impl Step for PlaceStep {
+    fn apply_step(&self, graph: &mut crate::graph::Graph) {
+        if let Some(node) = graph.node_weight_mut(self.idx) {
+            node.set_annotation(Some(format!("#{}", self.place)));
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        step_json("topo_place", self)
+    }
+
+    fn nodes(&self) -> Vec<NodeIndex> {
+        vec![self.idx]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortMethod {
+    // Repeatedly takes a node without incoming edges.
+    Kahn,
+    // Reverse of the order in which a depth-first search finishes nodes.
+    Dfs,
+}
+
+// Sorts the whole graph, the start node is ignored. A graph with a cycle can't be sorted,
+// one of its cycles is shown instead.
+pub struct TopologicalSort {
+    method: SortMethod,
+    steps: VecDeque<Box<dyn Step>>,
+    order: Vec<NodeIndex>,
+    cycle: Vec<NodeIndex>,
+}
+
+impl DirectedStepAlgorithm for TopologicalSort {
+    fn run<N, E: EdgeWeight>(
+        &mut self,
+        graph: &StableGraph<N, E, Directed>,
+        _start_idx: NodeIndex,
+    ) {
+        let unsorted = match self.method {
+            SortMethod::Kahn => self.kahn(graph),
+            SortMethod::Dfs => self.dfs(graph),
+        };
+        if !unsorted.is_empty() {
+            self.show_cycle(graph, &unsorted);
+        }
+    }
+
+    fn result(self) -> StepAlgorithmResult {
+        let outcome = if self.cycle.is_empty() {
+            AlgorithmOutcome::Order { order: self.order }
+        } else {
+            AlgorithmOutcome::Cycle { cycle: self.cycle }
+        };
+        StepAlgorithmResult::from_steps(self.steps).with_outcome(outcome)
+    }
+}
+
+impl TopologicalSort {
+    pub fn new(method: SortMethod) -> TopologicalSort {
+        TopologicalSort {
+            method,
+            steps: VecDeque::new(),
+            order: Vec::new(),
+            cycle: Vec::new(),
+        }
+    }
+
+    // Returns the nodes that are left over because of a cycle.
+    fn kahn<N, E>(&mut self, graph: &StableGraph<N, E, Directed>) -> HashSet<NodeIndex> {
+        let mut in_degrees = HashMap::new();
+        let mut queue = VecDeque::new();
+        for idx in graph.node_indices() {
+            let in_degree = graph.neighbors_directed(idx, Direction::Incoming).count();
+            in_degrees.insert(idx, in_degree);
+            self.steps
+                .push_back(Box::new(InDegreeStep::new(idx, in_degree)));
+        }
+        for idx in graph.node_indices() {
+            if in_degrees[&idx] == 0 {
+                queue.push_back(idx);
+                self.steps
+                    .push_back(Box::new(NodeStep::new(idx, NodeState::Queued)));
+            }
+        }
+
+        while let Some(idx) = queue.pop_front() {
+            in_degrees.remove(&idx);
+            self.order.push(idx);
+            self.steps
+                .push_back(Box::new(NodeStep::new(idx, NodeState::Visited)));
+            self.steps
+                .push_back(Box::new(PlaceStep::new(idx, self.order.len())));
+
+            let mut walker = graph.neighbors_directed(idx, Direction::Outgoing).detach();
+            while let Some((edge_idx, other_idx)) = walker.next(graph) {
+                let in_degree = match in_degrees.get_mut(&other_idx) {
+                    Some(in_degree) => in_degree,
+                    None => continue,
+                };
+                *in_degree -= 1;
+                self.steps.push_back(Box::new(dfs::EdgeStep::new(edge_idx)));
+                self.steps
+                    .push_back(Box::new(InDegreeStep::new(other_idx, *in_degree)));
+                if *in_degree == 0 {
+                    queue.push_back(other_idx);
+                    self.steps
+                        .push_back(Box::new(NodeStep::new(other_idx, NodeState::Queued)));
+                }
+            }
+        }
+        in_degrees.into_keys().collect()
+    }
+
+    fn dfs<N, E: EdgeWeight>(&mut self, graph: &StableGraph<N, E, Directed>) -> HashSet<NodeIndex> {
+        let mut dfs = Dfs::from_graph(graph);
+        for idx in graph.node_indices() {
+            if matches!(dfs.states().get(&idx), Some(dfs::NodeState::NotVisited)) {
+                dfs.run(graph, idx);
+            }
+        }
+        self.order = dfs.postorder().iter().rev().copied().collect();
+        self.steps = dfs.into_steps();
+
+        let unsorted = unsorted_nodes(graph);
+        if unsorted.is_empty() {
+            for (place, idx) in self.order.iter().enumerate() {
+                self.steps
+                    .push_back(Box::new(PlaceStep::new(*idx, place + 1)));
+            }
+        }
+        unsorted
+    }
+
+    // Every unsorted node has an edge from another unsorted node, so walking those edges
+    // backwards has to run into a cycle.
+    fn show_cycle<N, E>(
+        &mut self,
+        graph: &StableGraph<N, E, Directed>,
+        unsorted: &HashSet<NodeIndex>,
+    ) {
+        let mut idx = match unsorted.iter().min() {
+            Some(idx) => *idx,
+            None => return,
+        };
+        self.order.clear();
+
+        // Edges walked backwards, each with the node it leads to.
+        let mut walked: Vec<(EdgeIndex, NodeIndex)> = Vec::new();
+        let start = loop {
+            if let Some(start) = walked.iter().position(|(_, to)| *to == idx) {
+                break start;
+            }
+            let mut walker = graph.neighbors_directed(idx, Direction::Incoming).detach();
+            let (edge_idx, from) = match std::iter::from_fn(|| walker.next(graph))
+                .find(|(_, from)| unsorted.contains(from))
+            {
+                Some(edge) => edge,
+                None => return,
+            };
+            walked.push((edge_idx, idx));
+            idx = from;
+        };
+
+        for (edge_idx, to) in walked.drain(start..).rev() {
+            self.cycle.push(to);
+            self.steps.push_back(Box::new(CycleStep::new(edge_idx, to)));
+        }
+    }
+}
+
+// What Kahn's algorithm leaves over, without any steps.
+fn unsorted_nodes<N, E>(graph: &StableGraph<N, E, Directed>) -> HashSet<NodeIndex> {
+    let mut in_degrees = graph
+        .node_indices()
+        .map(|idx| {
+            let in_degree = graph.neighbors_directed(idx, Direction::Incoming).count();
+            (idx, in_degree)
+        })
+        .collect::<HashMap<_, _>>();
+    let mut sources = in_degrees
+        .iter()
+        .filter(|(_, in_degree)| **in_degree == 0)
+        .map(|(idx, _)| *idx)
+        .collect::<Vec<_>>();
+    while let Some(idx) = sources.pop() {
+        in_degrees.remove(&idx);
+        for other_idx in graph.neighbors_directed(idx, Direction::Outgoing) {
+            if let Some(in_degree) = in_degrees.get_mut(&other_idx) {
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    sources.push(other_idx);
+                }
+            }
+        }
+    }
+    in_degrees.into_keys().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::{SortMethod, TopologicalSort};
+    use crate::step_algorithms::{AlgorithmOutcome, DirectedStepAlgorithm};
+
+    fn sort(graph: &StableGraph<u32, u32>, method: SortMethod) -> AlgorithmOutcome {
+        let mut sort = TopologicalSort::new(method);
+        sort.run(graph, NodeIndex::new(0));
+        sort.result().outcome().clone()
+    }
+
+    fn with_edges(edges: &[(u32, u32)]) -> StableGraph<u32, u32> {
+        let mut graph = StableGraph::new();
+        for nr in 0..5 {
+            graph.add_node(nr);
+        }
+        for (from, to) in edges {
+            graph.add_edge(
+                NodeIndex::new(*from as usize),
+                NodeIndex::new(*to as usize),
+                1,
+            );
+        }
+        graph
+    }
+
+    fn order(indices: &[usize]) -> AlgorithmOutcome {
+        let order = indices.iter().map(|nr| NodeIndex::new(*nr)).collect();
+        AlgorithmOutcome::Order { order }
+    }
+
+    #[test]
+    fn sorts_a_dag() {
+        let graph = with_edges(&[(3, 1), (1, 0), (3, 4), (4, 0), (2, 4)]);
+
+        assert_eq!(sort(&graph, SortMethod::Kahn), order(&[2, 3, 4, 1, 0]));
+        assert_eq!(sort(&graph, SortMethod::Dfs), order(&[3, 2, 4, 1, 0]));
+    }
+
+    #[test]
+    fn finds_a_cycle() {
+        // 0 leads into the cycle 1 -> 2 -> 3 -> 1, 4 hangs off it.
+        let graph = with_edges(&[(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)]);
+        let cycle = AlgorithmOutcome::Cycle {
+            cycle: vec![NodeIndex::new(2), NodeIndex::new(3), NodeIndex::new(1)],
+        };
+
+        assert_eq!(sort(&graph, SortMethod::Kahn), cycle);
+        assert_eq!(sort(&graph, SortMethod::Dfs), cycle);
+        let self_loop = with_edges(&[(0, 1), (1, 2), (2, 2)]);
+        assert_eq!(
+            sort(&self_loop, SortMethod::Kahn),
+            AlgorithmOutcome::Cycle {
+                cycle: vec![NodeIndex::new(2)]
+            }
+        );
+    }
+}
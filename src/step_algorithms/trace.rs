@@ -0,0 +1,151 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::step_algorithm::Step;
+use super::topological_sort;
+use super::{astar, bellman_ford, bfs, biconnectivity, dfs, dijkstra, growth, scc, spanning_tree};
+
+fn parse<S: Step + DeserializeOwned>(value: Value) -> Result<Box<dyn Step>, serde_json::Error> {
+    Ok(Box::new(serde_json::from_value::<S>(value)?))
+}
+
+// Reverse of `Step::to_json`, every step name has to be listed here.
+pub fn step_from_json(value: Value) -> Result<Box<dyn Step>, String> {
+    let name = match value.get("step").and_then(Value::as_str) {
+        Some(name) => String::from(name),
+        None => return Err(String::from("step has no name")),
+    };
+    let step = match name.as_str() {
+        "bfs_node" => parse::<bfs::NodeStep>(value),
+        "bfs_edge" => parse::<bfs::EdgeStep>(value),
+        "dfs_node" => parse::<dfs::NodeStep>(value),
+        "dfs_edge" => parse::<dfs::EdgeStep>(value),
+        "dijkstra_edge" => parse::<dijkstra::EdgeStep>(value),
+        "dijkstra_distance" => parse::<dijkstra::DistanceStep>(value),
+        "scc_reverse" => parse::<scc::Reverse>(value),
+        "scc_paint_component" => parse::<scc::PaintComponent>(value),
+        "scc_reset_state" => parse::<scc::ResetState>(value),
+        "scc_enable_edges" => parse::<scc::EnableEdges>(value),
+        "astar_score" => parse::<astar::ScoreStep>(value),
+        "bellman_ford_round" => parse::<bellman_ford::RoundStep>(value),
+        "bellman_ford_cycle" => parse::<bellman_ford::CycleStep>(value),
+        "grow_node" => parse::<growth::ShowNode>(value),
+        "grow_edge" => parse::<growth::ShowEdge>(value),
+        "biconnected_edge" => parse::<biconnectivity::EdgeStep>(value),
+        "biconnected_low_link" => parse::<biconnectivity::LowLinkStep>(value),
+        "mst_edge" => parse::<spanning_tree::EdgeStep>(value),
+        "topo_in_degree" => parse::<topological_sort::InDegreeStep>(value),
+        "topo_place" => parse::<topological_sort::PlaceStep>(value),
+        _ => return Err(format!("unknown step '{}'", name)),
+    };
+    step.map_err(|err| format!("{} step: {}", name, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use petgraph::graph::NodeIndex;
+
+    use super::step_from_json;
+    use crate::graph::node::Node;
+    use crate::graph::{with_edge_type, Graph, GraphOnCanvas, Position};
+    use crate::step_algorithms::{grow, AStar, BellmanFord, Dfs, DirectedStepAlgorithm};
+    use crate::step_algorithms::{Biconnectivity, UndirectedStepAlgorithm};
+    use crate::step_algorithms::{Dijkstra, Kruskal, Prim, Scc, Step, StepAlgorithm};
+    use crate::step_algorithms::{SortMethod, StepAlgorithmResult, TopologicalSort};
+
+    fn round_trip(steps: &VecDeque<Box<dyn Step>>) -> VecDeque<Box<dyn Step>> {
+        steps
+            .iter()
+            .map(|step| step_from_json(step.to_json()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn steps_survive_json() {
+        let mut graph = Graph::new();
+        for nr in 0..4 {
+            graph.add_node(Node::new(Position::new(nr as f32, 0.)));
+        }
+        for (from, to) in [(0, 1), (1, 2), (2, 0), (2, 3)] {
+            graph.connect_nodes(NodeIndex::new(from), NodeIndex::new(to));
+        }
+
+        let mut dfs = Dfs::from_graph(&graph);
+        StepAlgorithm::run(&mut dfs, &graph, NodeIndex::new(0));
+        let mut dijkstra = Dijkstra::new(Some(NodeIndex::new(3)));
+        dijkstra.run(&graph, NodeIndex::new(0));
+        let mut scc = Scc::new();
+        scc.run(&graph, NodeIndex::new(0));
+        let mut bellman_ford = BellmanFord::new(Some(NodeIndex::new(3)));
+        bellman_ford.run(&graph, NodeIndex::new(0));
+        let mut astar = AStar::from_graph(&graph, NodeIndex::new(3));
+        astar.run(&graph, NodeIndex::new(0));
+        // The graph has a cycle, Kahn's in-degrees are shown before it is found.
+        let mut topological_sort = TopologicalSort::new(SortMethod::Kahn);
+        topological_sort.run(&graph, NodeIndex::new(0));
+        let undirected = with_edge_type(&graph);
+        let mut kruskal = Kruskal::new();
+        kruskal.run(&undirected, NodeIndex::new(0));
+        let mut prim = Prim::new();
+        prim.run(&undirected, NodeIndex::new(0));
+        let mut biconnectivity = Biconnectivity::new();
+        biconnectivity.run(&undirected, NodeIndex::new(0));
+        let growth = grow(
+            &mut graph.clone(),
+            &graph.node_indices().collect::<Vec<_>>(),
+        );
+
+        for result in [
+            dfs.result(),
+            dijkstra.result(),
+            scc.result(),
+            bellman_ford.result(),
+            astar.result(),
+            kruskal.result(),
+            prim.result(),
+            biconnectivity.result(),
+            topological_sort.result(),
+            growth,
+        ] {
+            assert!(result.steps().len() > 1);
+            assert!(round_trip(result.steps()) == *result.steps());
+            assert!(result.stale_steps(&graph).is_empty());
+        }
+    }
+
+    #[test]
+    fn reports_bad_steps() {
+        assert!(step_from_json(serde_json::json!({"idx": 0})).is_err());
+        assert!(step_from_json(serde_json::json!({"step": "sort", "idx": 0})).is_err());
+        assert!(step_from_json(serde_json::json!({"step": "bfs_node", "idx": 0})).is_err());
+    }
+
+    #[test]
+    fn finds_stale_steps() {
+        let mut graph = Graph::new();
+        for nr in 0..3 {
+            graph.add_node(Node::new(Position::new(nr as f32, 0.)));
+        }
+        graph.connect_nodes(NodeIndex::new(0), NodeIndex::new(1));
+        graph.connect_nodes(NodeIndex::new(1), NodeIndex::new(2));
+        let mut dfs = Dfs::from_graph(&graph);
+        StepAlgorithm::run(&mut dfs, &graph, NodeIndex::new(0));
+        let result = StepAlgorithmResult::from_steps(round_trip(dfs.result().steps()));
+
+        // Moving nodes keeps the run valid, removing one doesn't.
+        graph.move_node(NodeIndex::new(2), Position::new(5., 5.));
+        assert!(result.stale_steps(&graph).is_empty());
+        graph.remove_node(NodeIndex::new(2));
+        let stale = result.stale_steps(&graph);
+        assert!(!stale.is_empty());
+        assert!(stale
+            .iter()
+            .all(|step| step.nodes().contains(&NodeIndex::new(2)) || !step.edges().is_empty()));
+        assert_eq!(
+            stale.last().unwrap().to_string(),
+            format!("step {}: no node 2", stale.last().unwrap().step() + 1)
+        );
+    }
+}
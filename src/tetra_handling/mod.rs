@@ -1 +1,2 @@
+pub mod renderer;
 pub mod tetra_object;
@@ -0,0 +1,212 @@
+use std::f32::consts::PI;
+
+use tetra::graphics::mesh::{GeometryBuilder, Mesh, ShapeStyle};
+use tetra::graphics::text::{Font, Text};
+use tetra::graphics::{self, DrawParams, FilterMode};
+use tetra::math::Vec2;
+use tetra::Context;
+
+use projekt::constants::{
+    ANNOTATION_DISTANCE, BASE_ARROW_ARMS_SIZE, BASE_BORDER_SIZE, BASE_RADIUS, BASE_STROKE_WIDTH,
+    FONT_SIZE, FONT_SIZE_SQUARED, HIGHLIGHT_SCALE,
+};
+use projekt::graph::edge::Edge;
+use projekt::graph::node::Node;
+use projekt::graph::{Color, Graph, GraphOnCanvas, Position};
+
+use super::tetra_object::TetraObjectInfo;
+
+pub fn to_tetra_color(color: Color) -> graphics::Color {
+    graphics::Color::rgba(color.r, color.g, color.b, color.a)
+}
+
+// Meshes are shared by all nodes and edges and placed with draw params, so moving a node costs nothing.
+pub struct Renderer {
+    // To change colors this has to be separate
+    circle: Mesh,
+    border: Mesh,
+    // One unit long along the x axis, stretched to the length of the edge.
+    line: Mesh,
+    // Tip at the origin, pointing along the x axis.
+    arrow_head: Mesh,
+    // Creating font with every frame is expensive, since it has to cache the file again and again.
+    font: Font,
+}
+
+impl Renderer {
+    pub fn new(ctx: &mut Context) -> Renderer {
+        let arm = |angle: f32| Position::unit_x().rotated_z(angle) * BASE_ARROW_ARMS_SIZE;
+        let mut arrow_head = GeometryBuilder::new();
+        arrow_head
+            .polyline(
+                BASE_STROKE_WIDTH,
+                &[arm(PI * 3. / 4.), Position::zero(), arm(-PI * 3. / 4.)],
+            )
+            .unwrap();
+
+        Renderer {
+            circle: Mesh::circle(ctx, ShapeStyle::Fill, Position::zero(), BASE_RADIUS).unwrap(),
+            border: Mesh::circle(
+                ctx,
+                ShapeStyle::Stroke(BASE_BORDER_SIZE),
+                Position::zero(),
+                BASE_RADIUS,
+            )
+            .unwrap(),
+            line: Mesh::polyline(
+                ctx,
+                BASE_STROKE_WIDTH,
+                &[Position::zero(), Position::unit_x()],
+            )
+            .unwrap(),
+            arrow_head: arrow_head.build_mesh(ctx).unwrap(),
+            font: {
+                let mut font = Font::vector(
+                    ctx,
+                    "resources/fonts/JetBrainsMono-Regular.ttf",
+                    FONT_SIZE_SQUARED,
+                )
+                .unwrap();
+                font.set_filter_mode(ctx, FilterMode::Linear);
+                font
+            },
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, graph: &Graph, info: &TetraObjectInfo) {
+        let rotation = info.camera().rotation;
+        let mouse_position = info.camera().mouse_position(ctx);
+
+        for idx in graph.edge_indices() {
+            if let (Some((from, to)), Some(edge)) = (graph.edge_line(idx), graph.edge_weight(idx)) {
+                if edge.is_hidden() {
+                    continue;
+                }
+                self.draw_edge(ctx, edge, from, to, info.ui_data().directed());
+                if info.ui_data().show_weights() {
+                    self.draw_edge_text(ctx, edge, (from, to), rotation, mouse_position);
+                }
+            }
+        }
+
+        for node in graph.node_weights().filter(|node| !node.is_hidden()) {
+            self.draw_node(ctx, node, rotation, mouse_position);
+        }
+    }
+
+    fn draw_line(&self, ctx: &mut Context, from: Position, to: Position, color: Color) {
+        let direction = to - from;
+        self.line.draw(
+            ctx,
+            DrawParams::new()
+                .position(from)
+                .rotation(direction.y.atan2(direction.x))
+                .scale(Vec2::new(direction.magnitude(), 1.))
+                .color(to_tetra_color(color)),
+        );
+    }
+
+    fn draw_edge(
+        &self,
+        ctx: &mut Context,
+        edge: &Edge,
+        from: Position,
+        to: Position,
+        directed: bool,
+    ) {
+        if !directed {
+            self.draw_line(ctx, from, to, edge.color());
+            return;
+        }
+
+        let (from, to) = if edge.is_reversed() {
+            (to, from)
+        } else {
+            (from, to)
+        };
+        let (from, to) = Edge::arrow_line(from, to);
+        self.draw_line(ctx, from, to, edge.color());
+
+        let direction = to - from;
+        self.arrow_head.draw(
+            ctx,
+            DrawParams::new()
+                .position(to)
+                .rotation(direction.y.atan2(direction.x))
+                .color(to_tetra_color(edge.color())),
+        );
+    }
+
+    fn draw_edge_text(
+        &self,
+        ctx: &mut Context,
+        edge: &Edge,
+        (from, to): (Position, Position),
+        rotation: f32,
+        mouse_position: Position,
+    ) {
+        if edge.text().is_empty() {
+            return;
+        }
+
+        let mut text = Text::new(edge.text(), self.font.clone());
+        let mut text_params = DrawParams::new()
+            .scale(if Edge::text_contains(from, to, mouse_position) {
+                HIGHLIGHT_SCALE
+            } else {
+                Vec2::one()
+            })
+            .color(graphics::Color::BLACK);
+
+        // Same as for node text, centered, not rotated and scaled down.
+        text_params.origin = text.get_bounds(ctx).unwrap().bottom_right() / 2.;
+        text_params.position = Edge::text_position(from, to);
+        text_params.rotation = -rotation;
+        text_params.scale /= FONT_SIZE;
+        text.draw(ctx, text_params);
+    }
+
+    fn draw_node(&self, ctx: &mut Context, node: &Node, rotation: f32, mouse_position: Position) {
+        let params = DrawParams::new()
+            .scale(if node.is_highlighted() || node.contains(mouse_position) {
+                HIGHLIGHT_SCALE
+            } else {
+                Vec2::one()
+            })
+            .position(node.position());
+        self.circle
+            .draw(ctx, params.clone().color(to_tetra_color(node.color())));
+        self.border.draw(
+            ctx,
+            params.clone().color(to_tetra_color(node.border_color())),
+        );
+
+        if !node.node_text().is_empty() {
+            let mut text = Text::new(node.node_text(), self.font.clone());
+
+            // This turns on text wrapping after BASE_RADIUS
+            text.set_max_width(Some(BASE_RADIUS));
+
+            let mut text_params = params.color(graphics::Color::BLACK);
+
+            // We set the origin to the center of the text, so rotation will behave nicely.
+            text_params.origin = text.get_bounds(ctx).unwrap().bottom_right() / 2.;
+            // We do not want the text to rotate.
+            text_params.rotation = -rotation;
+            text_params.scale /= FONT_SIZE;
+            text.draw(ctx, text_params);
+        }
+
+        if let Some(annotation) = node.annotation() {
+            let mut text = Text::new(annotation, self.font.clone());
+            let mut text_params = DrawParams::new().color(graphics::Color::BLACK);
+            text_params.origin = text.get_bounds(ctx).unwrap().bottom_right() / 2.;
+            // Above the node on the screen, whatever the camera rotation is.
+            text_params.position =
+                node.position() + Position::new(0., -ANNOTATION_DISTANCE).rotated_z(-rotation);
+            text_params.rotation = -rotation;
+            text_params.scale /= FONT_SIZE;
+            text.draw(ctx, text_params);
+        }
+    }
+}
@@ -2,7 +2,6 @@ use tetra::graphics::Camera;
 
 use crate::game_state::AppMode;
 use crate::ui::ui_state::UiData;
-use tetra::Context;
 
 pub struct TetraObjectInfo {
     mode: AppMode,
@@ -43,9 +42,3 @@ impl TetraObjectInfo {
         &mut self.ui_data
     }
 }
-
-pub trait TetraObject {
-    fn draw(&mut self, ctx: &mut Context, info: &mut TetraObjectInfo);
-
-    fn update(&mut self, ctx: &mut Context, info: &mut TetraObjectInfo);
-}
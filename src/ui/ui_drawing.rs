@@ -1,16 +1,28 @@
-use egui_tetra::egui::{self, Button, Ui};
+use std::collections::HashSet;
+
+use egui_tetra::egui::text::{LayoutJob, TextFormat};
+use egui_tetra::egui::{self, Button, Color32, Key, TextEdit, TextStyle, Ui};
 use petgraph::graph::NodeIndex;
-use petgraph::{Directed, Undirected};
+use petgraph::Undirected;
 
-use crate::graph::random::generate;
-use crate::graph::GraphOnCanvas;
 use crate::input::input_state::{InputState, StateData};
+use projekt::constants::{MAX_PLAYBACK_SPEED, MIN_PLAYBACK_SPEED};
+use projekt::file_handling::edge_list::EdgeList;
+use projekt::graph::generators::families::{self, Family};
+use projekt::graph::generators::random::{self, RandomModel};
+use projekt::graph::layered::layered_positions;
+use projekt::graph::{with_edge_type, GraphOnCanvas};
+use projekt::history::Command;
 
 use crate::ui::ui_state::UiMode;
 
-use crate::step_algorithms::{Bfs, Dfs, Scc, StepAlgorithm};
-use crate::step_algorithms::{DirectedStepAlgorithm, UndirectedStepAlgorithm};
 use crate::GameState;
+use projekt::step_algorithms::{
+    has_negative_weights, AStar, AlgorithmOutcome, StepAlgorithmResult,
+};
+use projekt::step_algorithms::{BellmanFord, Bfs, Dfs, Dijkstra, Scc, StepAlgorithm};
+use projekt::step_algorithms::{Biconnectivity, SortMethod, TopologicalSort};
+use projekt::step_algorithms::{DirectedStepAlgorithm, Kruskal, Prim, UndirectedStepAlgorithm};
 use tetra::Context;
 
 fn controls_ui(game_state: &mut GameState, _ctx: &mut Context, egui_ctx: &egui::CtxRef) {
@@ -19,6 +31,10 @@ fn controls_ui(game_state: &mut GameState, _ctx: &mut Context, egui_ctx: &egui::
             game_state.tetra_info_mut().ui_data_mut().directed_mut(),
             "directed",
         );
+        ui.checkbox(
+            game_state.tetra_info_mut().ui_data_mut().show_weights_mut(),
+            "weights",
+        );
         ui.horizontal(|ui| {
             ui.selectable_value(
                 game_state.tetra_info_mut().ui_data_mut().state_mut(),
@@ -34,34 +50,191 @@ fn controls_ui(game_state: &mut GameState, _ctx: &mut Context, egui_ctx: &egui::
         if ui.button("reset state").clicked() {
             game_state.graph.reset_state();
         }
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(game_state.history().can_undo(), Button::new("Undo"))
+                .clicked()
+            {
+                game_state.undo();
+            }
+            if ui
+                .add_enabled(game_state.history().can_redo(), Button::new("Redo"))
+                .clicked()
+            {
+                game_state.redo();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("File");
+            ui.text_edit_singleline(game_state.tetra_info_mut().ui_data_mut().project_path_mut());
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                game_state.save_project();
+            }
+            if ui.button("Open").clicked() {
+                game_state.load_project();
+            }
+            if ui.button("Export SVG").clicked() {
+                game_state.export_svg();
+            }
+            if ui.button("Export TikZ").clicked() {
+                game_state.export_tikz();
+            }
+        });
+        ui.checkbox(
+            game_state
+                .tetra_info_mut()
+                .ui_data_mut()
+                .export_algorithm_state_mut(),
+            "TikZ with algorithm colors",
+        );
+        if let Some(message) = game_state.tetra_info().ui_data().status_message() {
+            ui.label(message);
+        }
     });
 }
 
-fn graph_editor_ui(game_state: &mut GameState, ctx: &mut Context, egui_ctx: &egui::CtxRef) {
-    if matches!(game_state.input_state, InputState::Select(_)) {
-        game_state.input_state = InputState::Move(StateData::default());
-    }
-    egui::Window::new("Edit").show(egui_ctx, |ui| {
+fn random_graph_ui(game_state: &mut GameState, ui: &mut Ui) {
+    let ui_data = game_state.tetra_info_mut().ui_data_mut();
+    ui.horizontal_wrapped(|ui| {
+        for nr in 0..ui_data.random_models_mut().len() {
+            let name = ui_data.random_models_mut()[nr].name();
+            ui.selectable_value(ui_data.random_model_mut(), nr, name);
+        }
+    });
+
+    // Counts after the node count, probabilities as sliders below them.
+    let nr = *ui_data.random_model_mut();
+    let (counts, probabilities) = match &mut ui_data.random_models_mut()[nr] {
+        RandomModel::Gnp { nodes, probability } => {
+            (vec![("Nodes", nodes)], vec![("Probability", probability)])
+        }
+        RandomModel::Gnm { nodes, edges } => (vec![("Nodes", nodes), ("Edges", edges)], Vec::new()),
+        RandomModel::PreferentialAttachment {
+            nodes,
+            edges_per_node,
+        } => (
+            vec![("Nodes", nodes), ("Edges per node", edges_per_node)],
+            Vec::new(),
+        ),
+        RandomModel::SmallWorld {
+            nodes,
+            neighbors,
+            rewiring,
+        } => (
+            vec![("Nodes", nodes), ("Neighbors on each side", neighbors)],
+            vec![("Rewiring", rewiring)],
+        ),
+    };
+    for (label, count) in counts {
         ui.horizontal(|ui| {
-            ui.label("Nodes");
-            ui.add(egui::DragValue::new(
-                game_state.tetra_info_mut().ui_data_mut().node_count_mut(),
-            ));
+            ui.label(label);
+            ui.add(egui::DragValue::new(count));
         });
+    }
+    for (label, probability) in probabilities {
+        ui.add(egui::Slider::new(probability, 0.0..=1.0).text(label));
+    }
+    ui.horizontal(|ui| {
+        ui.checkbox(ui_data.self_loops_mut(), "self-loops");
+        ui.checkbox(ui_data.multi_edges_mut(), "multi-edges");
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Seed");
+        ui.add(TextEdit::singleline(ui_data.seed_text_mut()).hint_text("random"));
+    });
+    let seed = ui_data.seed();
+    if seed.is_none() {
+        ui.label("The seed has to be a whole number.");
+    }
+    if let Some(last_seed) = ui_data.last_seed() {
         ui.horizontal(|ui| {
-            ui.label("Edges");
-            ui.add(egui::DragValue::new(
-                game_state.tetra_info_mut().ui_data_mut().edge_count_mut(),
-            ));
+            ui.label(format!("Last seed: {}", last_seed));
+            if ui.small_button("reuse").clicked() {
+                *ui_data.seed_text_mut() = last_seed.to_string();
+            }
         });
-        if ui.button("Generate").clicked() {
-            game_state.graph = generate(
-                ctx,
-                *game_state.tetra_info_mut().ui_data_mut().node_count_mut(),
-                *game_state.tetra_info_mut().ui_data_mut().edge_count(),
-                game_state.font(),
-            );
+    }
+
+    if let (Some(insert), Some(seed)) = (replace_or_insert_ui(ui), seed) {
+        let ui_data = game_state.tetra_info().ui_data();
+        let generated = random::generate(ui_data.random_model(), ui_data.random_options(), seed);
+        game_state.add_generated(generated, insert);
+        game_state.seed_layout(seed);
+        *game_state.tetra_info_mut().ui_data_mut().last_seed_mut() = Some(seed);
+    }
+}
+
+// Some(true) when the generated graph should go next to the existing one.
+fn replace_or_insert_ui(ui: &mut Ui) -> Option<bool> {
+    ui.horizontal(|ui| {
+        if ui.button("Replace").clicked() {
+            Some(false)
+        } else if ui.button("Insert").clicked() {
+            Some(true)
+        } else {
+            None
+        }
+    })
+    .inner
+}
+
+fn family_ui(game_state: &mut GameState, ui: &mut Ui) {
+    let ui_data = game_state.tetra_info_mut().ui_data_mut();
+    ui.horizontal_wrapped(|ui| {
+        for nr in 0..ui_data.families_mut().len() {
+            let name = ui_data.families_mut()[nr].name();
+            ui.selectable_value(ui_data.family_mut(), nr, name);
+        }
+    });
+
+    // Big parameters make huge graphs, the ranges keep them drawable.
+    let nr = *ui_data.family_mut();
+    let parameters = match &mut ui_data.families_mut()[nr] {
+        Family::Grid { rows, cols } => vec![("Rows", rows, 1..=30), ("Columns", cols, 1..=30)],
+        Family::Tree { branching, depth } => vec![
+            ("Branching factor", branching, 1..=6),
+            ("Depth", depth, 0..=6),
+        ],
+        Family::Cycle { nodes } => vec![("Nodes", nodes, 1..=200)],
+        Family::Wheel { rim } => vec![("Rim nodes", rim, 3..=200)],
+        Family::CompleteBipartite { left, right } => {
+            vec![("Left", left, 1..=30), ("Right", right, 1..=30)]
         }
+        Family::Hypercube { dimension } => vec![("Dimension", dimension, 0..=8)],
+        Family::Named(_) => Vec::new(),
+    };
+    for (label, value, range) in parameters {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.add(egui::DragValue::new(value).clamp_range(range));
+        });
+    }
+
+    if let Some(insert) = replace_or_insert_ui(ui) {
+        let generated = families::generate(game_state.tetra_info().ui_data().family());
+        game_state.add_generated(generated, insert);
+    }
+}
+
+fn graph_editor_ui(game_state: &mut GameState, _ctx: &mut Context, egui_ctx: &egui::CtxRef) {
+    if matches!(game_state.input_state, InputState::Select(_)) {
+        game_state.input_state = InputState::Move(StateData::default());
+    }
+    egui::Window::new("Edit").show(egui_ctx, |ui| {
+        ui.heading("Random graph");
+        random_graph_ui(game_state, ui);
+        ui.heading("Graph family");
+        family_ui(game_state, ui);
+        ui.checkbox(
+            game_state
+                .tetra_info_mut()
+                .ui_data_mut()
+                .animate_generation_mut(),
+            "animate generation",
+        );
         ui.heading("Edit Mode");
         ui.horizontal(|ui| {
             ui.selectable_value(&mut game_state.input_state, InputState::Add, "Add");
@@ -102,6 +275,20 @@ fn graph_editor_ui(game_state: &mut GameState, ctx: &mut Context, egui_ctx: &egu
                     .distance_mut(),
             ));
         });
+        ui.horizontal(|ui| {
+            ui.label("Theta");
+            ui.add(
+                egui::DragValue::new(
+                    game_state
+                        .tetra_info_mut()
+                        .ui_data_mut()
+                        .push_conf_mut()
+                        .theta_mut(),
+                )
+                .speed(0.01)
+                .clamp_range(0.0..=2.0),
+            );
+        });
         ui.label("Pull:");
         ui.horizontal(|ui| {
             ui.label("Value");
@@ -126,44 +313,252 @@ fn graph_editor_ui(game_state: &mut GameState, ctx: &mut Context, egui_ctx: &egu
 
         // This is done dirty, just to be able to quickly build nontrivial graph.
         if ui.button("clique").clicked() {
-            for node in game_state.graph.node_indices() {
-                for node_other in game_state.graph.node_indices() {
-                    if node != node_other {
-                        game_state.graph.connect_nodes(ctx, node, node_other);
+            let command = Command::clique(&mut game_state.graph);
+            game_state.push_command(command);
+        }
+    });
+}
+
+fn edge_list_ui(game_state: &mut GameState, _ctx: &mut Context, egui_ctx: &egui::CtxRef) {
+    egui::Window::new("Edge list").show(egui_ctx, |ui| {
+        let error_lines = game_state
+            .tetra_info()
+            .ui_data()
+            .edge_list_errors()
+            .iter()
+            .map(|error| error.line())
+            .collect::<HashSet<_>>();
+
+        let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+            let mut job = LayoutJob::default();
+            for (nr, line) in text.split_inclusive('\n').enumerate() {
+                let mut format = TextFormat {
+                    style: TextStyle::Monospace,
+                    color: ui.visuals().text_color(),
+                    ..Default::default()
+                };
+                if error_lines.contains(&nr) {
+                    format.background = Color32::from_rgb(120, 20, 20);
+                }
+                job.append(line, 0., format);
+            }
+            job.wrap_width = wrap_width;
+            ui.fonts().layout_job(job)
+        };
+
+        let response = ui.add(
+            TextEdit::multiline(
+                game_state
+                    .tetra_info_mut()
+                    .ui_data_mut()
+                    .edge_list_text_mut(),
+            )
+            .code_editor()
+            .desired_rows(10)
+            .layouter(&mut layouter),
+        );
+
+        if response.gained_focus() {
+            let before = game_state.graph.clone();
+            let ui_data = game_state.tetra_info_mut().ui_data_mut();
+            *ui_data.edge_list_before_mut() = Some(before);
+            ui_data.edge_list_positions_mut().clear();
+        }
+        // Mistakes show up while typing, the graph only changes on enter or once the user is done.
+        let enter = response.has_focus() && ui.input().key_pressed(Key::Enter);
+        if response.changed() || enter || response.lost_focus() {
+            let text = game_state
+                .tetra_info_mut()
+                .ui_data_mut()
+                .edge_list_text_mut()
+                .clone();
+            let parsed = EdgeList::parse(&text).and_then(|edge_list| {
+                edge_list.check(&game_state.graph)?;
+                Ok(edge_list)
+            });
+            match parsed {
+                Ok(edge_list) => {
+                    if enter || response.lost_focus() {
+                        apply_edge_list(game_state, &edge_list);
                     }
+                    game_state
+                        .tetra_info_mut()
+                        .ui_data_mut()
+                        .set_edge_list_errors(Vec::new());
                 }
+                Err(errors) => game_state
+                    .tetra_info_mut()
+                    .ui_data_mut()
+                    .set_edge_list_errors(errors),
             }
         }
+        if response.lost_focus() {
+            let before = game_state
+                .tetra_info_mut()
+                .ui_data_mut()
+                .edge_list_before_mut()
+                .take();
+            if let Some(before) = before {
+                if EdgeList::from_graph(&before) != EdgeList::from_graph(&game_state.graph) {
+                    let after = game_state.graph.clone();
+                    game_state.push_command(Command::ReplaceGraph { before, after });
+                }
+            }
+        }
+
+        // While the user types, the text is theirs. Otherwise it mirrors the graph.
+        if !response.has_focus()
+            && game_state
+                .tetra_info()
+                .ui_data()
+                .edge_list_errors()
+                .is_empty()
+        {
+            *game_state
+                .tetra_info_mut()
+                .ui_data_mut()
+                .edge_list_text_mut() = EdgeList::from_graph(&game_state.graph).to_text();
+        }
+
+        for error in game_state.tetra_info().ui_data().edge_list_errors() {
+            ui.colored_label(
+                Color32::RED,
+                format!("line {}: {}", error.line() + 1, error.message()),
+            );
+        }
     });
 }
 
+fn apply_edge_list(game_state: &mut GameState, edge_list: &EdgeList) {
+    let is_directed = game_state.tetra_info().ui_data().directed();
+    let mut positions = std::mem::take(
+        game_state
+            .tetra_info_mut()
+            .ui_data_mut()
+            .edge_list_positions_mut(),
+    );
+    edge_list.apply_keeping_positions(&mut game_state.graph, is_directed, &mut positions);
+    *game_state
+        .tetra_info_mut()
+        .ui_data_mut()
+        .edge_list_positions_mut() = positions;
+}
+
+// Undirected graphs are viewed as such by the algorithm.
+fn run_in_place<T: StepAlgorithm>(game_state: &GameState, algo: &mut T, idx: NodeIndex) {
+    if game_state.tetra_info().ui_data().directed() {
+        algo.run(&game_state.graph, idx);
+    } else {
+        algo.run(&with_edge_type::<_, _, Undirected>(&game_state.graph), idx);
+    }
+}
+
+fn run_on_graph<T: StepAlgorithm>(
+    game_state: &GameState,
+    mut algo: T,
+    idx: NodeIndex,
+) -> StepAlgorithmResult {
+    run_in_place(game_state, &mut algo, idx);
+    algo.result()
+}
+
 fn create_algo_button<T: StepAlgorithm>(
     game_state: &mut GameState,
     selected_idx_opt: Option<NodeIndex>,
     ui: &mut Ui,
-    mut algo: T,
+    algo: T,
     button_name: &str,
 ) {
     if ui
-        .add_enabled(
-            matches!(selected_idx_opt, Some(_)),
-            Button::new(button_name),
-        )
+        .add_enabled(selected_idx_opt.is_some(), Button::new(button_name))
         .clicked()
     {
         if let Some(idx) = selected_idx_opt {
-            let is_directed = game_state.tetra_info_mut().ui_data_mut().directed();
-            let graph_copy = game_state.graph.clone();
-            if is_directed {
-                algo.run(&graph_copy.into_edge_type::<Directed>(), idx);
-            } else {
-                algo.run(&graph_copy.into_edge_type::<Undirected>(), idx);
-            }
-            game_state.add_algorithm(algo.result());
+            let result = run_on_graph(game_state, algo, idx);
+            game_state.add_algorithm(result);
         }
     }
 }
 
+fn expanded_count(result: &StepAlgorithmResult) -> usize {
+    match result.outcome() {
+        AlgorithmOutcome::Search { expanded, .. } => expanded.len(),
+        _ => 0,
+    }
+}
+
+const NEGATIVE_WEIGHTS: &str = "negative weights, use bellman-ford";
+
+// A* needs a target. Dijkstra runs alongside with the same edge costs and counts the nodes it
+// expands until it reaches the same target, to show how many the heuristic saves.
+fn astar_ui(
+    game_state: &mut GameState,
+    idx_opt: Option<NodeIndex>,
+    target_opt: Option<NodeIndex>,
+    ui: &mut Ui,
+) {
+    ui.horizontal(|ui| {
+        let negative = has_negative_weights(&game_state.graph);
+        let button = ui.add_enabled(
+            idx_opt.is_some() && target_opt.is_some() && !negative,
+            Button::new("a*"),
+        );
+        if let (true, Some(idx), Some(target)) = (button.clicked(), idx_opt, target_opt) {
+            let mut astar = AStar::from_graph(&game_state.graph, target);
+            run_in_place(game_state, &mut astar, idx);
+            let mut dijkstra = Dijkstra::new(Some(target)).with_costs(astar.costs().clone());
+            run_in_place(game_state, &mut dijkstra, idx);
+            let result = astar.result();
+            let counts = (expanded_count(&result), dijkstra.expanded_until_target());
+            game_state.add_algorithm(result);
+            *game_state
+                .tetra_info_mut()
+                .ui_data_mut()
+                .expanded_counts_mut() = Some(counts);
+        }
+        if negative {
+            ui.label(NEGATIVE_WEIGHTS);
+        } else if let Some((astar, dijkstra)) = game_state.tetra_info().ui_data().expanded_counts()
+        {
+            ui.label(format!("expanded {} nodes, dijkstra {}", astar, dijkstra));
+        }
+    });
+}
+
+// Both methods sort the whole graph. A sorted graph can be laid out in layers afterwards.
+fn topological_sort_ui(game_state: &mut GameState, ui: &mut Ui) {
+    let enabled = game_state.tetra_info().ui_data().directed() && game_state.graph.node_count() > 0;
+    ui.horizontal(|ui| {
+        ui.label("topological sort");
+        for (method, name) in [(SortMethod::Kahn, "kahn"), (SortMethod::Dfs, "dfs")] {
+            if ui.add_enabled(enabled, Button::new(name)).clicked() {
+                let mut sort = TopologicalSort::new(method);
+                sort.run(&game_state.graph, NodeIndex::new(0));
+                let result = sort.result();
+                let layers = match result.outcome() {
+                    AlgorithmOutcome::Order { order } => {
+                        Some(layered_positions(&game_state.graph, order))
+                    }
+                    _ => None,
+                };
+                game_state.add_algorithm(result);
+                if let (true, Some(layers)) =
+                    (game_state.tetra_info().ui_data().arrange_layers(), layers)
+                {
+                    game_state.arrange_when_finished(layers);
+                }
+            }
+        }
+        ui.checkbox(
+            game_state
+                .tetra_info_mut()
+                .ui_data_mut()
+                .arrange_layers_mut(),
+            "arrange in layers",
+        );
+    });
+}
+
 fn create_directed_algo_button<T: DirectedStepAlgorithm>(
     game_state: &mut GameState,
     selected_idx_opt: Option<NodeIndex>,
@@ -173,7 +568,7 @@ fn create_directed_algo_button<T: DirectedStepAlgorithm>(
 ) {
     if ui
         .add_enabled(
-            matches!(selected_idx_opt, Some(_)) && game_state.tetra_info().ui_data().directed(),
+            selected_idx_opt.is_some() && game_state.tetra_info().ui_data().directed(),
             Button::new(button_name),
         )
         .clicked()
@@ -185,7 +580,7 @@ fn create_directed_algo_button<T: DirectedStepAlgorithm>(
     }
 }
 
-fn _create_undirected_algo_button<T: UndirectedStepAlgorithm>(
+fn create_undirected_algo_button<T: UndirectedStepAlgorithm>(
     game_state: &mut GameState,
     selected_idx_opt: Option<NodeIndex>,
     ui: &mut Ui,
@@ -194,14 +589,13 @@ fn _create_undirected_algo_button<T: UndirectedStepAlgorithm>(
 ) {
     if ui
         .add_enabled(
-            matches!(selected_idx_opt, Some(_)) && !game_state.tetra_info().ui_data().directed(),
+            selected_idx_opt.is_some() && !game_state.tetra_info().ui_data().directed(),
             Button::new(button_name),
         )
         .clicked()
     {
         if let Some(idx) = selected_idx_opt {
-            let graph_copy = game_state.graph.clone().into_edge_type::<Undirected>();
-            algo.run(&graph_copy.into_edge_type::<Undirected>(), idx);
+            algo.run(&with_edge_type(&game_state.graph), idx);
             game_state.add_algorithm(algo.result());
         }
     }
@@ -212,13 +606,25 @@ fn algorithm_ui(game_state: &mut GameState, _ctx: &mut Context, egui_ctx: &egui:
         game_state.input_state = InputState::Select(StateData::default());
     }
 
-    let idx_opt = if let InputState::Select(data) = &mut game_state.input_state {
-        data.selected_node
+    let (idx_opt, target_opt) = if let InputState::Select(data) = &mut game_state.input_state {
+        (data.selected_node, data.target_node)
     } else {
-        None
+        (None, None)
     };
 
     egui::Window::new("Show algorithms").show(egui_ctx, |ui| {
+        ui.horizontal(|ui| {
+            match target_opt {
+                Some(target) => ui.label(format!("Target: {}", target.index())),
+                None if idx_opt.is_some() => ui.label("Target: click a second node"),
+                None => ui.label("Target: none"),
+            };
+            if ui.button("clear").clicked() {
+                if let InputState::Select(data) = &mut game_state.input_state {
+                    data.target_node = None;
+                }
+            }
+        });
         create_algo_button(
             game_state,
             idx_opt,
@@ -233,14 +639,152 @@ fn algorithm_ui(game_state: &mut GameState, _ctx: &mut Context, egui_ctx: &egui:
             Bfs::from_graph(&game_state.graph),
             "bfs",
         );
+        ui.horizontal(|ui| {
+            let negative = has_negative_weights(&game_state.graph);
+            ui.add_enabled_ui(!negative, |ui| {
+                create_algo_button(
+                    game_state,
+                    idx_opt,
+                    ui,
+                    Dijkstra::new(target_opt),
+                    "dijkstra",
+                );
+            });
+            if negative {
+                ui.label(NEGATIVE_WEIGHTS);
+            }
+        });
+        create_algo_button(
+            game_state,
+            idx_opt,
+            ui,
+            BellmanFord::new(target_opt),
+            "bellman-ford",
+        );
+        astar_ui(game_state, idx_opt, target_opt, ui);
         create_directed_algo_button(
             game_state,
             game_state.graph.node_indices().next(),
             ui,
             Scc::new(),
             "strongly connected components",
-        )
+        );
+        topological_sort_ui(game_state, ui);
+        create_undirected_algo_button(
+            game_state,
+            game_state.graph.node_indices().next(),
+            ui,
+            Kruskal::new(),
+            "kruskal",
+        );
+        create_undirected_algo_button(game_state, idx_opt, ui, Prim::new(), "prim");
+        create_undirected_algo_button(
+            game_state,
+            idx_opt,
+            ui,
+            Biconnectivity::new(),
+            "articulation points and bridges",
+        );
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    game_state.algorithm_mut().is_some(),
+                    Button::new("save run"),
+                )
+                .clicked()
+            {
+                game_state.save_run();
+            }
+            if ui.button("replay saved run").clicked() {
+                game_state.replay_run();
+            }
+        });
+
+        ui.separator();
+        playback_ui(game_state, ui);
+    });
+}
+
+fn playback_ui(game_state: &mut GameState, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label("Speed");
+        ui.add(
+            egui::Slider::new(
+                game_state
+                    .tetra_info_mut()
+                    .ui_data_mut()
+                    .playback_speed_mut(),
+                MIN_PLAYBACK_SPEED..=MAX_PLAYBACK_SPEED,
+            )
+            .logarithmic(true)
+            .suffix(" steps/s"),
+        );
+    });
+
+    let speed = game_state.tetra_info().ui_data().playback_speed();
+    let (algorithm, graph) = match game_state.algorithm_mut() {
+        Some(algorithm) => algorithm,
+        None => {
+            ui.label("No algorithm to show");
+            return;
+        }
+    };
+    algorithm.set_speed(speed);
+
+    ui.label(format!(
+        "step {} of {}",
+        algorithm.current(),
+        algorithm.steps().len()
+    ));
+    let mut step = algorithm.current();
+    if ui
+        .add(egui::Slider::new(&mut step, 0..=algorithm.steps().len()).show_value(false))
+        .changed()
+    {
+        algorithm.pause();
+        algorithm.jump_to(graph, step);
+    }
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(algorithm.current() > 0, Button::new("back"))
+            .clicked()
+        {
+            algorithm.pause();
+            algorithm.step_back(graph);
+        }
+        if algorithm.is_playing() {
+            if ui.button("pause").clicked() {
+                algorithm.pause();
+            }
+        } else if ui
+            .add_enabled(!algorithm.is_finished(), Button::new("play"))
+            .clicked()
+        {
+            algorithm.play();
+        }
+        if ui
+            .add_enabled(!algorithm.is_finished(), Button::new("step"))
+            .clicked()
+        {
+            algorithm.pause();
+            algorithm.step_forward(graph);
+        }
+        if ui
+            .add_enabled(!algorithm.is_finished(), Button::new("run to end"))
+            .clicked()
+        {
+            algorithm.run_to_end(graph);
+        }
+        if ui.button("restart").clicked() {
+            algorithm.restart(graph);
+        }
     });
+    if let (true, AlgorithmOutcome::SpanningTree { weight, .. }) =
+        (algorithm.is_finished(), algorithm.outcome())
+    {
+        ui.label(format!("total weight: {}", weight));
+    }
 }
 
 // Disable editing when algorithm is running, disable algorithm when editing
@@ -248,6 +792,7 @@ pub fn create_ui(game_state: &mut GameState, ctx: &mut Context, egui_ctx: &egui:
     controls_ui(game_state, ctx, egui_ctx);
     if matches!(game_state.tetra_info().ui_data().state(), UiMode::Edit) {
         graph_editor_ui(game_state, ctx, egui_ctx);
+        edge_list_ui(game_state, ctx, egui_ctx);
     } else {
         algorithm_ui(game_state, ctx, egui_ctx);
     }
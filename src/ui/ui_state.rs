@@ -1,9 +1,15 @@
-use crate::constants::{
-    PULL_FORCE_FORCE_AT_TWICE_DISTANCE, PULL_FORCE_MIN_DISTANCE, PUSH_FORCE_DISTANCE,
-    PUSH_FORCE_FORCE,
+use petgraph::graph::NodeIndex;
+use projekt::constants::{
+    BASE_PLAYBACK_SPEED, PULL_FORCE_FORCE_AT_TWICE_DISTANCE, PULL_FORCE_MIN_DISTANCE,
+    PUSH_FORCE_DISTANCE, PUSH_FORCE_FORCE,
 };
-use crate::graph::gravity::{PullForceConfig, PushForceConfig};
+use projekt::file_handling::edge_list::LineError;
+use projekt::graph::generators::families::{Family, NamedGraph};
+use projekt::graph::generators::random::{RandomModel, RandomOptions};
+use projekt::graph::gravity::{PullForceConfig, PushForceConfig};
+use projekt::graph::{Graph, Position};
 use std::borrow::BorrowMut;
+use std::collections::HashMap;
 
 #[derive(PartialEq)]
 pub enum UiMode {
@@ -15,28 +21,112 @@ pub struct UiData {
     mode: UiMode,
 
     is_directed: bool,
+    show_weights: bool,
 
     //   force:
     push_conf: PushForceConfig,
     pull_conf: PullForceConfig,
 
     //   random-gen:
-    node_count: u32,
-    edge_count: u32,
+    // One of each model, so switching between them keeps their parameters.
+    random_models: Vec<RandomModel>,
+    random_model: usize,
+    self_loops: bool,
+    multi_edges: bool,
+    // Empty for a new seed every time.
+    seed_text: String,
+    last_seed: Option<u64>,
+    // Like the random models, with the named graphs at the end.
+    families: Vec<Family>,
+    family: usize,
+    // Generated graphs grow node by node instead of appearing at once.
+    animate_generation: bool,
+
+    //   algorithm playback:
+    playback_speed: f32,
+    // Nodes the last A* run expanded, and Dijkstra to the same target.
+    expanded_counts: Option<(usize, usize)>,
+    // A finished topological sort moves the nodes into layers.
+    arrange_layers: bool,
+
+    //   project-file:
+    project_path: String,
+    status_message: Option<String>,
+    // Whether TikZ figures show the algorithm's colors or the graph from before it.
+    export_algorithm_state: bool,
+
+    //   edge-list panel:
+    edge_list_text: String,
+    edge_list_errors: Vec<LineError>,
+    // The graph from when the panel got focus, all edits until it loses focus undo at once.
+    edge_list_before: Option<Graph>,
+    // Where the nodes were during that time, so a node that is removed and added again
+    // comes back in its place.
+    edge_list_positions: HashMap<NodeIndex, Position>,
 }
 
 impl UiData {
     pub fn new() -> UiData {
         UiData {
             is_directed: true,
+            show_weights: false,
             push_conf: PushForceConfig::new(PUSH_FORCE_FORCE, PUSH_FORCE_DISTANCE),
             pull_conf: PullForceConfig::new(
                 PULL_FORCE_MIN_DISTANCE,
                 PULL_FORCE_FORCE_AT_TWICE_DISTANCE,
             ),
-            node_count: 10,
-            edge_count: 15,
+            random_models: vec![
+                RandomModel::Gnp {
+                    nodes: 10,
+                    probability: 0.3,
+                },
+                RandomModel::Gnm {
+                    nodes: 10,
+                    edges: 15,
+                },
+                RandomModel::PreferentialAttachment {
+                    nodes: 20,
+                    edges_per_node: 2,
+                },
+                RandomModel::SmallWorld {
+                    nodes: 20,
+                    neighbors: 2,
+                    rewiring: 0.2,
+                },
+            ],
+            random_model: 1,
+            self_loops: false,
+            multi_edges: false,
+            seed_text: String::new(),
+            last_seed: None,
+            families: vec![
+                Family::Grid { rows: 4, cols: 5 },
+                Family::Tree {
+                    branching: 2,
+                    depth: 3,
+                },
+                Family::Cycle { nodes: 8 },
+                Family::Wheel { rim: 8 },
+                Family::CompleteBipartite { left: 3, right: 4 },
+                Family::Hypercube { dimension: 3 },
+                Family::Named(NamedGraph::Petersen),
+                Family::Named(NamedGraph::Heawood),
+                Family::Named(NamedGraph::Octahedron),
+                Family::Named(NamedGraph::Dodecahedron),
+            ],
+            family: 0,
+            animate_generation: false,
             mode: UiMode::Edit,
+            playback_speed: BASE_PLAYBACK_SPEED,
+            expanded_counts: None,
+            arrange_layers: true,
+            project_path: String::from("graph.json"),
+            status_message: None,
+            export_algorithm_state: true,
+            edge_list_text: String::new(),
+            edge_list_errors: Vec::new(),
+            edge_list_before: None,
+            edge_list_positions: HashMap::new(),
         }
     }
 
@@ -56,16 +146,100 @@ impl UiData {
         &mut self.is_directed
     }
 
-    pub fn node_count_mut(&mut self) -> &mut u32 {
-        &mut self.node_count
+    pub fn playback_speed(&self) -> f32 {
+        self.playback_speed
+    }
+
+    pub fn playback_speed_mut(&mut self) -> &mut f32 {
+        &mut self.playback_speed
+    }
+
+    pub fn expanded_counts(&self) -> Option<(usize, usize)> {
+        self.expanded_counts
+    }
+
+    pub fn expanded_counts_mut(&mut self) -> &mut Option<(usize, usize)> {
+        &mut self.expanded_counts
+    }
+
+    pub fn arrange_layers(&self) -> bool {
+        self.arrange_layers
+    }
+
+    pub fn arrange_layers_mut(&mut self) -> &mut bool {
+        &mut self.arrange_layers
+    }
+
+    pub fn show_weights(&self) -> bool {
+        self.show_weights
+    }
+
+    pub fn show_weights_mut(&mut self) -> &mut bool {
+        &mut self.show_weights
+    }
+
+    pub fn random_models_mut(&mut self) -> &mut Vec<RandomModel> {
+        &mut self.random_models
+    }
+
+    pub fn random_model_mut(&mut self) -> &mut usize {
+        &mut self.random_model
+    }
+
+    pub fn random_model(&self) -> &RandomModel {
+        &self.random_models[self.random_model]
+    }
+
+    pub fn self_loops_mut(&mut self) -> &mut bool {
+        &mut self.self_loops
+    }
+
+    pub fn multi_edges_mut(&mut self) -> &mut bool {
+        &mut self.multi_edges
+    }
+
+    pub fn random_options(&self) -> RandomOptions {
+        RandomOptions::new(self.is_directed, self.self_loops, self.multi_edges)
+    }
+
+    pub fn seed_text_mut(&mut self) -> &mut String {
+        &mut self.seed_text
+    }
+
+    // The seed to generate the next graph with, None if the text isn't a number.
+    pub fn seed(&self) -> Option<u64> {
+        match self.seed_text.trim() {
+            "" => Some(rand::random()),
+            text => text.parse().ok(),
+        }
+    }
+
+    pub fn last_seed(&self) -> Option<u64> {
+        self.last_seed
+    }
+
+    pub fn last_seed_mut(&mut self) -> &mut Option<u64> {
+        &mut self.last_seed
+    }
+
+    pub fn animate_generation(&self) -> bool {
+        self.animate_generation
+    }
+
+    pub fn animate_generation_mut(&mut self) -> &mut bool {
+        &mut self.animate_generation
     }
 
-    pub fn edge_count_mut(&mut self) -> &mut u32 {
-        &mut self.edge_count
+    pub fn families_mut(&mut self) -> &mut Vec<Family> {
+        &mut self.families
     }
 
-    pub fn edge_count(&mut self) -> &mut u32 {
-        &mut self.edge_count
+    pub fn family_mut(&mut self) -> &mut usize {
+        &mut self.family
+    }
+
+    pub fn family(&self) -> &Family {
+        &self.families[self.family]
     }
 
     pub fn push_conf_mut(&mut self) -> &mut PushForceConfig {
@@ -87,6 +261,50 @@ impl UiData {
     pub fn pull_conf(&self) -> &PullForceConfig {
         &self.pull_conf
     }
+
+    pub fn project_path(&self) -> &str {
+        &self.project_path
+    }
+
+    pub fn project_path_mut(&mut self) -> &mut String {
+        &mut self.project_path
+    }
+
+    pub fn status_message(&self) -> Option<&String> {
+        self.status_message.as_ref()
+    }
+
+    pub fn set_status_message(&mut self, message: Option<String>) {
+        self.status_message = message;
+    }
+
+    pub fn export_algorithm_state(&self) -> bool {
+        self.export_algorithm_state
+    }
+
+    pub fn export_algorithm_state_mut(&mut self) -> &mut bool {
+        &mut self.export_algorithm_state
+    }
+
+    pub fn edge_list_text_mut(&mut self) -> &mut String {
+        &mut self.edge_list_text
+    }
+
+    pub fn edge_list_errors(&self) -> &Vec<LineError> {
+        &self.edge_list_errors
+    }
+
+    pub fn set_edge_list_errors(&mut self, errors: Vec<LineError>) {
+        self.edge_list_errors = errors;
+    }
+
+    pub fn edge_list_before_mut(&mut self) -> &mut Option<Graph> {
+        &mut self.edge_list_before
+    }
+
+    pub fn edge_list_positions_mut(&mut self) -> &mut HashMap<NodeIndex, Position> {
+        &mut self.edge_list_positions
+    }
 }
 
 impl Default for UiData {